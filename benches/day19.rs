@@ -0,0 +1,30 @@
+use aoc_2022::day19::{max_geodes, parse};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const EXAMPLE: &str = "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.
+Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsidian robot costs 3 ore and 8 clay. Each geode robot costs 3 ore and 12 obsidian.";
+
+fn horizons(c: &mut Criterion) {
+    let blueprints = parse(EXAMPLE).unwrap();
+
+    let mut group = c.benchmark_group("day19");
+    group.sample_size(10);
+    for minutes in [24, 32, 40] {
+        group.bench_with_input(
+            BenchmarkId::new("max_geodes", minutes),
+            &minutes,
+            |b, &m| {
+                b.iter(|| {
+                    blueprints
+                        .iter()
+                        .map(|bp| max_geodes(bp, m).geodes)
+                        .collect::<Vec<_>>()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, horizons);
+criterion_main!(benches);
@@ -0,0 +1,89 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// A simulation that repeats itself, found by `detect`.
+///
+/// `history[n]` is the metric after `n` steps, the states after `start` and
+/// `start + len` steps share the same key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub len: usize,
+    pub history: Vec<i64>,
+}
+
+impl Cycle {
+    /// The metric after `step` steps, assuming it grows by the same amount
+    /// every time round the cycle.
+    pub fn extrapolate(&self, step: u64) -> i64 {
+        let step = step as usize;
+        if step < self.history.len() {
+            return self.history[step];
+        }
+
+        let (rounds, offset) = (
+            (step - self.start) / self.len,
+            (step - self.start) % self.len,
+        );
+        let gain = self.history[self.start + self.len] - self.history[self.start];
+        self.history[self.start + offset] + rounds as i64 * gain
+    }
+}
+
+/// Steps `state` until its `key` repeats, recording `metric` after every step
+/// (and before the first one).
+///
+/// The key has to capture everything deciding the future of the simulation,
+/// otherwise the extrapolation is wrong.
+pub fn detect<S, K, F, G, M>(state: &mut S, mut step: F, mut key: G, mut metric: M) -> Cycle
+where
+    K: Eq + Hash,
+    F: FnMut(&mut S),
+    G: FnMut(&S) -> K,
+    M: FnMut(&S) -> i64,
+{
+    let mut seen = HashMap::from([(key(state), 0)]);
+    let mut history = vec![metric(state)];
+
+    loop {
+        step(state);
+        history.push(metric(state));
+
+        let n = history.len() - 1;
+        if let Some(&start) = seen.get(&key(state)) {
+            return Cycle {
+                start,
+                len: n - start,
+                history,
+            };
+        }
+        seen.insert(key(state), n);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // counts 0, 1, .., 4 then loops through 5, 6, 7 and sums the counter
+    fn step((x, sum): &mut (i64, i64)) {
+        *x = if *x < 7 { *x + 1 } else { 5 };
+        *sum += *x;
+    }
+
+    #[test]
+    fn check_cycle() {
+        let mut state = (0, 0);
+        let cycle = super::detect(&mut state, step, |&(x, _)| x, |&(_, sum)| sum);
+        assert_eq!((cycle.start, cycle.len), (5, 3));
+
+        let mut state = (0, 0);
+        for n in 0..100 {
+            assert_eq!(cycle.extrapolate(n), state.1);
+            step(&mut state);
+        }
+
+        // 15 for the tail then 18 every 3 steps
+        assert_eq!(
+            cycle.extrapolate(1_000_000_000_001),
+            15 + 333_333_333_332 * 18
+        );
+    }
+}
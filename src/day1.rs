@@ -1,22 +1,22 @@
 use itertools::Itertools;
 
+use crate::error::{lines, number, ParseError};
+
 type Calories = Vec<u32>;
 
 #[aoc_generator(day1)]
-pub fn parse_calories(input: &str) -> Vec<Calories> {
+pub fn parse_calories(input: &str) -> Result<Vec<Calories>, ParseError> {
     let mut calories = Vec::new();
     calories.push(Vec::new());
 
-    for line in input.lines() {
-        if let Ok(n) = line.parse::<u32>() {
-            if let Some(v) = calories.last_mut() {
-                v.push(n);
-            }
-        } else {
+    for (n, line) in lines(input) {
+        if line.is_empty() {
             calories.push(Vec::new());
+        } else if let Some(v) = calories.last_mut() {
+            v.push(number(n, line, line)?);
         }
     }
-    calories
+    Ok(calories)
 }
 
 #[aoc(day1, part1)]
@@ -1,24 +1,20 @@
+use crate::{
+    error::{lines, number, ParseError},
+    ocr,
+};
+
 pub enum Instruction {
     Noop,
     Addx(i32),
 }
 
 #[aoc_generator(day10)]
-pub fn parse_instruction(input: &str) -> Vec<Instruction> {
-    input
-        .lines()
-        .map(|l| {
-            let mut split = l.split(' ');
-            match split.next() {
-                Some("noop") => Instruction::Noop,
-                Some("addx") => Instruction::Addx(
-                    split
-                        .next()
-                        .map(|s| s.parse::<i32>().expect("error parsing addx"))
-                        .unwrap(),
-                ),
-                _ => unreachable!(),
-            }
+pub fn parse_instruction(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    lines(input)
+        .map(|(n, l)| match l.split_once(' ') {
+            None if l == "noop" => Ok(Instruction::Noop),
+            Some(("addx", x)) => Ok(Instruction::Addx(number(n, l, x)?)),
+            _ => Err(ParseError::line(n, l, "expected `noop` or `addx n`")),
         })
         .collect()
 }
@@ -86,11 +82,10 @@ fn draw_pixel(cycle: usize, sprite: i32, screen: &mut [Vec<char>]) {
     })
 }
 
-#[aoc(day10, part2)]
-pub fn solve_part2(instructions: &[Instruction]) -> String {
-    const WIDTH: usize = 40;
-    const HEIGHT: usize = 6;
+const WIDTH: usize = 40;
+const HEIGHT: usize = 6;
 
+fn render(instructions: &[Instruction]) -> Vec<Vec<char>> {
     let mut screen = vec![vec!['.'; WIDTH]; HEIGHT];
 
     let mut sprite = 1;
@@ -112,6 +107,23 @@ pub fn solve_part2(instructions: &[Instruction]) -> String {
         }
     }
 
+    screen
+}
+
+/// The letters the program draws on the CRT, `None` if they can't be read.
+pub fn letters(instructions: &[Instruction]) -> Option<String> {
+    ocr::decode(&render(instructions))
+}
+
+// returns the letters shown on the screen, or the raw screen if they can't be read
+#[aoc(day10, part2)]
+pub fn solve_part2(instructions: &[Instruction]) -> String {
+    if let Some(code) = letters(instructions) {
+        return code;
+    }
+
+    let screen = render(instructions);
+
     screen
         .iter()
         .enumerate()
@@ -283,13 +295,14 @@ noop";
 
     #[test]
     fn check_part1() {
-        let generated = super::parse_instruction(EXAMPLE);
+        let generated = super::parse_instruction(EXAMPLE).unwrap();
         assert_eq!(super::solve_part1(&generated), 13140);
     }
 
     #[test]
     fn check_part2() {
-        let generated = super::parse_instruction(EXAMPLE);
+        let generated = super::parse_instruction(EXAMPLE).unwrap();
+        assert_eq!(super::letters(&generated), None);
         assert_eq!(
             super::solve_part2(&generated),
             "
@@ -301,4 +314,43 @@ noop";
 #######.......#######.......#######....."
         );
     }
+
+    // one `addx` per two pixels, keeping the sprite over the lit ones only
+    fn program(picture: &str) -> String {
+        let pixels = picture.lines().flat_map(|l| l.chars()).collect::<Vec<_>>();
+        // the sprite starts over the first two pixels
+        assert_eq!(pixels[..2], ['#', '#']);
+
+        let mut sprite = 1;
+        let mut program = vec![];
+        for (i, pair) in pixels.chunks(2).enumerate().skip(1) {
+            let x = (2 * i % super::WIDTH) as i32;
+            let next = match pair {
+                ['#', '#'] => x,
+                ['#', _] => x - 1,
+                [_, '#'] => x + 2,
+                _ => -10,
+            };
+            // moves the sprite once the previous two pixels are drawn
+            program.push(format!("addx {}", next - sprite));
+            sprite = next;
+        }
+        program.push("addx 0".to_owned());
+        program.join("\n")
+    }
+
+    #[test]
+    fn check_letters() {
+        // Y and J touch, there is no blank column between them
+        let picture = "\
+####.#..#.####.####.#...#..##..##..###..
+#....#..#....#....#.#...#...#.#..#.#..#.
+###..#..#...#....#...#.#....#.#..#.###..
+#....#..#..#....#.....#.....#.####.#..#.
+#....#..#.#....#......#..#..#.#..#.#..#.
+#.....##..####.####...#...##..#..#.###..";
+        let generated = super::parse_instruction(&program(picture)).unwrap();
+        assert_eq!(super::letters(&generated), Some("FUZZYJAB".to_owned()));
+        assert_eq!(super::solve_part2(&generated), "FUZZYJAB");
+    }
 }
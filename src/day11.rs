@@ -1,5 +1,6 @@
-use itertools::Itertools;
-use std::{collections::VecDeque, str::FromStr};
+use std::collections::VecDeque;
+
+use crate::error::{lines, number, ParseError};
 
 #[derive(Clone)]
 pub enum Operation {
@@ -22,59 +23,83 @@ pub struct Monkey {
     test: Test,
 }
 
-fn parse_collect<C, T>(input_str: &str) -> C
-where
-    T: FromStr,
-    C: FromIterator<T>,
-{
-    input_str
-        .replace(',', "")
-        .split(' ')
-        .filter_map(|s| s.parse::<T>().ok())
-        .collect::<C>()
-}
-
-fn parse_first<T>(input_str: &str) -> Option<T>
-where
-    T: FromStr,
-{
-    input_str.split(' ').find_map(|s| s.parse::<T>().ok())
+fn field<'a>(n: usize, l: &'a str, prefix: &str) -> Result<&'a str, ParseError> {
+    l.trim_start()
+        .strip_prefix(prefix)
+        .ok_or_else(|| ParseError::line(n, l, format!("expected `{prefix}`")))
 }
 
 #[aoc_generator(day11)]
-pub fn parse_monkeys(input: &str) -> Vec<Monkey> {
-    input
-        .replace("  ", "")
-        .lines()
-        .filter(|l| !l.is_empty())
-        .tuples()
-        .map(|(_, items, operation, divisor, if_true, if_false)| {
-            let mut op_split = operation.split(' ').skip(4);
-            let op = match op_split.next() {
-                Some("*") => match op_split.next().and_then(|x| x.parse::<u32>().ok()) {
-                    Some(n) => Operation::Mult(n),
-                    None => Operation::Square,
-                },
-                Some("+") => Operation::Add(
-                    op_split
-                        .next()
-                        .and_then(|n| n.parse::<u32>().ok())
-                        .expect("no addend found"),
-                ),
-                _ => unreachable!(),
-            };
+pub fn parse_monkeys(input: &str) -> Result<Vec<Monkey>, ParseError> {
+    let eof = input.lines().count() + 1;
+    let mut lines = lines(input).filter(|(_, l)| !l.is_empty());
 
-            Monkey {
-                items: parse_collect(items),
-                op,
-                test: Test {
-                    divisor: parse_first(divisor).expect("no divisor found"),
-                    if_true: parse_first(if_true).expect("no if true found"),
-                    if_false: parse_first(if_false).expect("no if false found"),
-                },
-            }
-        })
-        .collect()
+    let mut monkeys = vec![];
+    // where every throw is aimed, checked once all monkeys are known
+    let mut targets = vec![];
+    while let Some((n, l)) = lines.next() {
+        field(n, l, "Monkey ")?;
+
+        let mut next = |prefix: &str| match lines.next() {
+            Some((n, l)) => field(n, l, prefix).map(|s| (n, l, s)),
+            None => Err(ParseError::end(eof, "", format!("expected `{prefix}`"))),
+        };
+
+        let (n, l, items) = next("Starting items: ")?;
+        let items = items
+            .split(", ")
+            .map(|s| number(n, l, s))
+            .collect::<Result<_, _>>()?;
+
+        let (n, l, operation) = next("Operation: new = old ")?;
+        let op = match operation.split_once(' ') {
+            Some(("*", "old")) => Operation::Square,
+            Some(("*", x)) => Operation::Mult(number(n, l, x)?),
+            Some(("+", x)) => Operation::Add(number(n, l, x)?),
+            _ => return Err(ParseError::token(n, l, operation, "unknown operation")),
+        };
+
+        let (n, l, divisor) = next("Test: divisible by ")?;
+        let divisor = match number(n, l, divisor)? {
+            0 => return Err(ParseError::token(n, l, divisor, "can't divide by 0")),
+            d => d,
+        };
+        let (n, l, token) = next("If true: throw to monkey ")?;
+        let if_true = number(n, l, token)?;
+        targets.push((monkeys.len(), if_true, n, l, token));
+        let (n, l, token) = next("If false: throw to monkey ")?;
+        let if_false = number(n, l, token)?;
+        targets.push((monkeys.len(), if_false, n, l, token));
+
+        monkeys.push(Monkey {
+            items,
+            op,
+            test: Test {
+                divisor,
+                if_true,
+                if_false,
+            },
+        });
+    }
+
+    if monkeys.len() < 2 {
+        return Err(ParseError::end(eof, "", "expected at least 2 monkeys"));
+    }
+    for (from, to, n, l, token) in targets {
+        if to >= monkeys.len() {
+            return Err(ParseError::token(n, l, token, "no such monkey"));
+        }
+        if to == from {
+            return Err(ParseError::token(
+                n,
+                l,
+                token,
+                "a monkey can't throw to itself",
+            ));
+        }
+    }
+
+    Ok(monkeys)
 }
 
 fn round(monkeys: &mut [Monkey], modulo: Option<usize>, inspection: &mut [usize]) {
@@ -165,15 +190,70 @@ Monkey 3:
     If true: throw to monkey 0
     If false: throw to monkey 1";
 
+    #[test]
+    fn check_malformed() {
+        let err = super::parse_monkeys(&EXAMPLE.replace("old + 6", "old ^ 6"))
+            .err()
+            .unwrap();
+        assert_eq!((err.line, err.column, err.text.as_str()), (10, 24, "^ 6"));
+
+        let truncated = EXAMPLE.lines().take(26).collect::<Vec<_>>().join("\n");
+        let err = super::parse_monkeys(&truncated).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 27, column 1: expected `If false: throw to monkey `"
+        );
+
+        let err = super::parse_monkeys(&EXAMPLE.replace("by 19", "by 0"))
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 11, column 22: can't divide by 0, found `0`"
+        );
+
+        let err = super::parse_monkeys(&EXAMPLE.replace("monkey 3\n\n", "monkey 4\n\n"))
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 6, column 31: no such monkey, found `4`"
+        );
+
+        let err = super::parse_monkeys(
+            &EXAMPLE.replace("If false: throw to monkey 0", "If false: throw to monkey 1"),
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 13, column 31: a monkey can't throw to itself, found `1`"
+        );
+
+        let single = EXAMPLE.lines().take(6).collect::<Vec<_>>().join("\n");
+        let err = super::parse_monkeys(&single.replace("monkey 2", "monkey 0"))
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 7, column 1: expected at least 2 monkeys"
+        );
+        let err = super::parse_monkeys("").err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 1, column 1: expected at least 2 monkeys"
+        );
+    }
+
     #[test]
     fn check_part1() {
-        let generated = super::parse_monkeys(EXAMPLE);
+        let generated = super::parse_monkeys(EXAMPLE).unwrap();
         assert_eq!(super::solve_part1(&generated), 10605);
     }
 
     #[test]
     fn check_part2() {
-        let generated = super::parse_monkeys(EXAMPLE);
+        let generated = super::parse_monkeys(EXAMPLE).unwrap();
         assert_eq!(super::solve_part2(&generated), 2713310158);
     }
 }
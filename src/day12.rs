@@ -1,135 +1,104 @@
-use std::collections::VecDeque;
+use crate::{error::ParseError, grid::Grid, search, vec::Vec2};
 
-type Coord = (usize, usize);
+type Coord = Vec2;
 
 pub struct HeightMap {
-    heights: Vec<Vec<u8>>,
+    heights: Grid<u8>,
     start: Coord,
     end: Coord,
 }
 
 impl HeightMap {
-    fn width(&self) -> usize {
-        self.heights[0].len()
+    pub fn start(&self) -> Coord {
+        self.start
     }
 
-    fn height(&self) -> usize {
-        self.heights.len()
+    pub fn end(&self) -> Coord {
+        self.end
     }
 
-    fn get(&self, (i, j): Coord) -> Option<&u8> {
-        self.heights.get(i).and_then(|row| row.get(j))
-    }
+    fn neighbors(&self, p: Coord) -> impl Iterator<Item = Coord> + '_ {
+        let h = self.heights[p];
 
-    fn neighbors(&self, (i, j): Coord) -> impl Iterator<Item = Coord> + '_ {
-        let h = self.heights[i][j];
-
-        [(-1, 0), (1, 0), (0, -1), (0, 1)]
-            .iter()
-            .filter_map(move |(di, dj)| {
-                let ii = (i as i32) + di;
-                let jj = (j as i32) + dj;
-
-                if let Some(nh) = self.get((ii as usize, jj as usize)) {
-                    // reversed conditions - since we find the path in reverse
-                    if *nh == h - 1 || *nh >= h {
-                        Some((ii as usize, jj as usize))
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            })
+        self.heights.neighbors4(p).filter(move |&n| {
+            let nh = self.heights[n];
+            // reversed conditions - since we find the path in reverse
+            nh == h - 1 || nh >= h
+        })
     }
 }
 
-fn find_mutate<F>(parsed: &mut [Vec<u8>], fun: F) -> Option<(usize, usize)>
-where
-    F: Fn(&mut u8) -> bool,
-{
-    parsed.iter_mut().enumerate().find_map(|(i, row)| {
-        row.iter_mut()
-            .enumerate()
-            .find_map(|(j, c)| if fun(c) { Some(j) } else { None })
-            .map(|j| (i, j))
-    })
-}
-
 #[aoc_generator(day12)]
-pub fn parse_heightmap(input: &str) -> HeightMap {
-    let mut heights = Vec::from_iter(input.lines().map(|l| l.as_bytes().to_vec()));
-
-    let start = find_mutate(&mut heights, |c| {
-        if *c == b'S' {
-            *c = b'a';
-            return true;
-        }
-        false
-    })
-    .expect("couldnt find start");
-
-    let end = find_mutate(&mut heights, |c| {
-        if *c == b'E' {
-            *c = b'z';
-            return true;
-        }
-        false
-    })
-    .expect("couldnt find end");
-
-    HeightMap {
+pub fn parse_heightmap(input: &str) -> Result<HeightMap, ParseError> {
+    let mut heights = Grid::parse(input, "a height a-z, S or E", |c| match c {
+        'a'..='z' | 'S' | 'E' => Some(c as u8),
+        _ => None,
+    })?;
+    let eof = heights.height() + 1;
+
+    let start = heights
+        .find(|&c| c == b'S')
+        .ok_or_else(|| ParseError::end(eof, "", "couldn't find start S"))?;
+    heights[start] = b'a';
+
+    let end = heights
+        .find(|&c| c == b'E')
+        .ok_or_else(|| ParseError::end(eof, "", "couldn't find end E"))?;
+    heights[end] = b'z';
+
+    Ok(HeightMap {
         heights,
         start,
         end,
-    }
-}
-
-#[derive(Copy, Clone)]
-struct Path {
-    cost: usize,
-    coord: Coord,
+    })
 }
 
-fn distance<F>(map: &HeightMap, termination: F) -> usize
+/// Shortest route to the end from the closest square matching `from`, the
+/// route starts at that square and ends at `E`.
+pub fn route<F>(map: &HeightMap, from: F) -> Option<Vec<Coord>>
 where
     F: Fn(Coord) -> bool,
 {
-    let mut dist = vec![vec![std::usize::MAX; map.width()]; map.height()];
-    // uniform distance - no prioqueue needed
-    let mut queue = VecDeque::<Path>::new();
-
-    // compute all distances from the end (thus solving part 1 & 2 together)
-    dist[map.end.0][map.end.1] = 0;
-    queue.push_back(Path {
-        cost: 0,
-        coord: map.end,
-    });
-
-    // BFS
-    while let Some(Path { cost, coord }) = queue.pop_front() {
-        if termination(coord) {
-            return cost;
-        }
-
-        if cost > dist[coord.0][coord.1] {
-            continue;
-        }
-
-        for ncoord in map.neighbors(coord) {
-            let next = Path {
-                cost: cost + 1,
-                coord: ncoord,
-            };
-
-            if next.cost < dist[next.coord.0][next.coord.1] {
-                queue.push_back(next);
-                dist[next.coord.0][next.coord.1] = next.cost;
-            }
-        }
+    // searched from the end (thus solving part 1 & 2 the same way)
+    let path = search::bfs(map.end, |&p| map.neighbors(p), |&p| from(p))?;
+    Some(path.nodes.into_iter().rev().collect())
+}
+
+/// Draws `route` over the map like the puzzle text, every square on it points
+/// to the next one.
+///
+/// Panics unless every square of `route` is next to the one before it, as in
+/// the routes [`route`] returns.
+pub fn render(map: &HeightMap, route: &[Coord]) -> String {
+    let mut grid = map.heights.map(|_| '.');
+    for w in route.windows(2) {
+        let step = w[1] - w[0];
+        assert!(
+            step.x.abs() + step.y.abs() == 1,
+            "{:?} and {:?} aren't adjacent",
+            w[0],
+            w[1]
+        );
+        grid[w[0]] = match step {
+            d if d == Vec2::UP => '^',
+            d if d == Vec2::DOWN => 'v',
+            d if d == Vec2::LEFT => '<',
+            d if d == Vec2::RIGHT => '>',
+            _ => unreachable!(),
+        };
     }
+    grid[map.end] = 'E';
+    grid.to_string()
+}
 
-    unreachable!("couldnt find termination criteria!");
+fn distance<F>(map: &HeightMap, from: F) -> usize
+where
+    F: Fn(Coord) -> bool,
+{
+    route(map, from)
+        .expect("couldnt find termination criteria!")
+        .len()
+        - 1
 }
 
 #[aoc(day12, part1)]
@@ -139,7 +108,7 @@ pub fn solve_part1(map: &HeightMap) -> usize {
 
 #[aoc(day12, part2)]
 pub fn solve_part2(map: &HeightMap) -> usize {
-    distance(map, |coord| *map.get(coord).unwrap() == b'a')
+    distance(map, |coord| map.heights[coord] == b'a')
 }
 
 #[cfg(test)]
@@ -153,13 +122,39 @@ abdefghi";
 
     #[test]
     fn check_part1() {
-        let generated = super::parse_heightmap(EXAMPLE);
+        let generated = super::parse_heightmap(EXAMPLE).unwrap();
         assert_eq!(super::solve_part1(&generated), 31);
     }
 
     #[test]
     fn check_part2() {
-        let generated = super::parse_heightmap(EXAMPLE);
+        let generated = super::parse_heightmap(EXAMPLE).unwrap();
         assert_eq!(super::solve_part2(&generated), 29);
     }
+
+    #[test]
+    fn check_route() {
+        let map = super::parse_heightmap(EXAMPLE).unwrap();
+        let route = super::route(&map, |p| p == map.start()).unwrap();
+        assert_eq!(route.len(), 32);
+        assert_eq!(route.first(), Some(&map.start()));
+        assert_eq!(route.last(), Some(&map.end()));
+        assert_eq!(
+            super::render(&map, &route),
+            ">>vv<<<<
+..vvv<<^
+..vv>E^^
+..v>>>^^
+..>>>>>^
+"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "aren't adjacent")]
+    fn check_render_gap() {
+        let map = super::parse_heightmap(EXAMPLE).unwrap();
+        let route = super::route(&map, |p| p == map.start()).unwrap();
+        super::render(&map, &[route[0], route[2]]);
+    }
 }
@@ -1,7 +1,9 @@
-use std::cmp::Ordering;
+use std::{cmp::Ordering, iter::Peekable, str::CharIndices};
 
 use itertools::{EitherOrBoth, Itertools};
 
+use crate::error::{lines, number, ParseError};
+
 enum Order {
     Right,
     Wrong,
@@ -14,21 +16,34 @@ pub enum Packet {
 }
 
 impl Packet {
-    fn parse(chars: &mut impl Iterator<Item = char>) -> Option<Self> {
+    // None marks the end of a list
+    fn parse(
+        n: usize,
+        l: &str,
+        chars: &mut Peekable<CharIndices>,
+    ) -> Result<Option<Self>, ParseError> {
         match chars.next() {
-            Some('[') => {
+            Some((_, '[')) => {
                 let mut packets = Vec::new();
-                while let Some(packet) = Packet::parse(chars) {
+                while let Some(packet) = Packet::parse(n, l, chars)? {
                     packets.push(packet)
                 }
-                Some(Packet::List(packets))
+                Ok(Some(Packet::List(packets)))
+            }
+            Some((_, ']')) => Ok(None),
+            Some((_, ',')) => Packet::parse(n, l, chars),
+            Some((i, c)) if c.is_ascii_digit() => {
+                let mut j = i + 1;
+                while let Some((k, _)) = chars.next_if(|(_, d)| d.is_ascii_digit()) {
+                    j = k + 1;
+                }
+                Ok(Some(Packet::Integer(number(n, l, &l[i..j])?)))
             }
-            Some(']') => None,
-            Some('a') => Some(Packet::Integer(10)), // kek
-            Some(n) => Some(Packet::Integer(
-                n.to_digit(10).expect("couldn't parse integer!"),
-            )),
-            _ => unreachable!(),
+            Some((i, c)) => {
+                let c = &l[i..i + c.len_utf8()];
+                Err(ParseError::token(n, l, c, "expected a list or integer"))
+            }
+            None => Err(ParseError::end(n, l, "expected `]`")),
         }
     }
 
@@ -79,11 +94,18 @@ impl PartialOrd for Packet {
 }
 
 #[aoc_generator(day13)]
-pub fn parse(input: &str) -> Vec<Packet> {
-    input
-        .lines()
-        .filter(|l| !l.is_empty())
-        .filter_map(|xs| Packet::parse(&mut xs.replace("10", "a").replace(',', "").chars()))
+pub fn parse(input: &str) -> Result<Vec<Packet>, ParseError> {
+    lines(input)
+        .filter(|(_, l)| !l.is_empty())
+        .map(|(n, l)| {
+            let mut chars = l.char_indices().peekable();
+            let packet = Packet::parse(n, l, &mut chars)?
+                .ok_or_else(|| ParseError::token(n, l, l, "unexpected `]`"))?;
+            if let Some((i, _)) = chars.next() {
+                return Err(ParseError::token(n, l, &l[i..], "trailing characters"));
+            }
+            Ok(packet)
+        })
         .collect()
 }
 
@@ -103,7 +125,7 @@ pub fn solve_part1(packets: &[Packet]) -> usize {
 
 #[aoc(day13, part2)]
 pub fn solve_part2(packets: &[Packet]) -> usize {
-    let divider = parse("[[2]]\n[[6]]");
+    let divider = parse("[[2]]\n[[6]]").unwrap();
 
     let a = &divider[0];
     let b = &divider[1];
@@ -146,13 +168,13 @@ mod tests {
 
     #[test]
     fn check_part1() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part1(&generated), 13);
     }
 
     #[test]
     fn check_part2() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part2(&generated), 140);
     }
 }
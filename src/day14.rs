@@ -2,36 +2,44 @@ use std::{cmp::max, cmp::min, collections::HashSet};
 
 use itertools::Itertools;
 
-type Coord = (i32, i32);
+use crate::{
+    error::{lines, number, ParseError},
+    vec::Vec2,
+};
+
+type Coord = Vec2;
+
+const SOURCE: Coord = Vec2 { x: 500, y: 0 };
 type Rocks = HashSet<Coord>;
 
 #[aoc_generator(day14)]
-pub fn parse(input: &str) -> (Rocks, i32) {
+pub fn parse(input: &str) -> Result<(Rocks, i32), ParseError> {
     let mut rocks = HashSet::<Coord>::new();
+    let mut abyss = None;
+
+    for (n, l) in lines(input) {
+        let path = l
+            .split(" -> ")
+            .map(|p| {
+                let (x, y) = p
+                    .split_once(',')
+                    .ok_or_else(|| ParseError::token(n, l, p, "expected a point x,y"))?;
+                Ok(Vec2::new(number(n, l, x)?, number(n, l, y)?))
+            })
+            .collect::<Result<Vec<Coord>, _>>()?;
+
+        for (&a, &b) in path.iter().tuple_windows() {
+            for x in min(a.x, b.x)..=max(a.x, b.x) {
+                for y in min(a.y, b.y)..=max(a.y, b.y) {
+                    rocks.insert(Vec2::new(x, y));
+                }
+            }
+        }
+        abyss = path.iter().map(|p| p.y).chain(abyss).max();
+    }
 
-    let abyss = input
-        .replace(" -> ", ",")
-        .lines()
-        .map(|l| {
-            l.split(',')
-                .filter_map(|n| n.parse::<i32>().ok())
-                .tuples()
-                .tuple_windows()
-                .map(|((a, b), (c, d))| {
-                    for x in min(a, c)..=max(a, c) {
-                        for y in min(b, d)..=max(b, d) {
-                            rocks.insert((x, y));
-                        }
-                    }
-                    max(b, d)
-                })
-                .max()
-                .unwrap()
-        })
-        .max()
-        .unwrap();
-
-    (rocks, abyss)
+    let abyss = abyss.ok_or_else(|| ParseError::end(1, "", "expected a rock path"))?;
+    Ok((rocks, abyss))
 }
 
 fn simulate_grain<F>(
@@ -43,16 +51,21 @@ fn simulate_grain<F>(
 where
     F: Fn(Coord) -> bool,
 {
-    let mut grain: Coord = (500, 0);
+    let mut grain = SOURCE;
 
     while sim_while(grain) {
-        if let Some(next) = [(0, 1), (-1, 1), (1, 1)].iter().find_map(|(dx, dy)| {
-            let next = (grain.0 + dx, grain.1 + dy);
+        let falls = [
+            Vec2::DOWN,
+            Vec2::DOWN + Vec2::LEFT,
+            Vec2::DOWN + Vec2::RIGHT,
+        ];
+        if let Some(next) = falls.into_iter().find_map(|d| {
+            let next = grain + d;
             if rocks.contains(&next) || sand.contains(&next) {
                 return None;
             }
             if let Some(y) = floor {
-                if next.1 == y {
+                if next.y == y {
                     return None;
                 }
             }
@@ -71,7 +84,7 @@ where
 pub fn solve_part1((rocks, abyss): &(Rocks, i32)) -> usize {
     let mut sand = HashSet::<Coord>::new();
 
-    while let Some(coord) = simulate_grain(|grain| grain.1 < *abyss, None, rocks, &sand) {
+    while let Some(coord) = simulate_grain(|grain| grain.y < *abyss, None, rocks, &sand) {
         sand.insert(coord);
     }
 
@@ -83,7 +96,7 @@ pub fn solve_part2((rocks, abyss): &(Rocks, i32)) -> usize {
     let mut sand = HashSet::<Coord>::new();
 
     while let Some(coord) =
-        simulate_grain(|_| !sand.contains(&(500, 0)), Some(abyss + 2), rocks, &sand)
+        simulate_grain(|_| !sand.contains(&SOURCE), Some(abyss + 2), rocks, &sand)
     {
         sand.insert(coord);
     }
@@ -98,13 +111,13 @@ mod tests {
 
     #[test]
     fn check_part1() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part1(&generated), 24);
     }
 
     #[test]
     fn check_part2() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part2(&generated), 93);
     }
 }
@@ -2,18 +2,23 @@ use itertools::Itertools;
 use rayon::prelude::{IntoParallelIterator, ParallelIterator};
 use std::ops::Range;
 
+use crate::{
+    error::{lines, number, ParseError},
+    vec::Vec2,
+};
+
 pub struct Diamond {
-    center: (i32, i32),
+    center: Vec2,
     radius: u32,
 }
 
 impl Diamond {
     fn segment_by_y(&self, y: i32) -> Option<Range<i32>> {
-        let dy = self.center.1.abs_diff(y);
+        let dy = self.center.y.abs_diff(y);
         if dy > self.radius {
             None
         } else {
-            let x = self.center.0;
+            let x = self.center.x;
             let dx = self.radius - dy;
             let x1 = x - dx as i32;
             let x2 = x + dx as i32;
@@ -23,28 +28,30 @@ impl Diamond {
 }
 
 #[aoc_generator(day15)]
-pub fn parse(input: &str) -> Vec<Diamond> {
-    input
-        .replace("x=", "")
-        .replace("y=", "")
-        .replace([':', ','], "")
-        .lines()
-        .map(|l| {
-            l.split(' ')
-                .filter_map(|s| s.parse::<i32>().ok())
-                .tuples()
-                .map(|(x1, y1, x2, y2)| {
-                    let p = (x1, y1);
-                    let d = x1.abs_diff(x2) + y1.abs_diff(y2);
-                    Diamond {
-                        center: p,
-                        radius: d,
-                    }
-                })
-                .next()
-                .expect("error parsing a line!")
+pub fn parse(input: &str) -> Result<Vec<Diamond>, ParseError> {
+    lines(input)
+        .map(|(n, l)| {
+            let point = |s: &str| -> Result<Vec2, ParseError> {
+                let (x, y) = s
+                    .strip_prefix("x=")
+                    .and_then(|s| s.split_once(", y="))
+                    .ok_or_else(|| ParseError::token(n, l, s, "expected a point x=.., y=.."))?;
+                Ok(Vec2::new(number(n, l, x)?, number(n, l, y)?))
+            };
+
+            let (sensor, beacon) = l
+                .strip_prefix("Sensor at ")
+                .and_then(|s| s.split_once(": closest beacon is at "))
+                .ok_or_else(|| ParseError::line(n, l, "expected a sensor and beacon"))?;
+            let sensor = point(sensor)?;
+            let beacon = point(beacon)?;
+
+            Ok(Diamond {
+                center: sensor,
+                radius: sensor.manhattan(beacon) as u32,
+            })
         })
-        .collect_vec()
+        .collect()
 }
 
 fn find_occupied(y: i32, diamonds: &[Diamond]) -> Vec<Range<i32>> {
@@ -114,13 +121,13 @@ Sensor at x=20, y=1: closest beacon is at x=15, y=3";
 
     #[test]
     fn check_part1() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part1(&generated), 26);
     }
 
     #[test]
     fn check_part2() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part2(&generated), 56000011);
     }
 }
@@ -1,163 +1,394 @@
-use scanf::sscanf;
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt::Display};
+
+use bit_set::BitSet;
+use itertools::Itertools;
+
+use crate::{
+    error::{lines, number, ParseError},
+    search,
+};
 
 pub struct Node {
+    name: String,
     flow: u32,
-    nonzero_idx: usize,
     neighbors: Vec<usize>,
 }
 
-type Parsed = (Vec<Node>, usize);
+pub type Parsed = (Vec<Node>, usize);
 
 #[aoc_generator(day16)]
-pub fn parse(input: &str) -> (Vec<Node>, usize) {
+pub fn parse(input: &str) -> Result<(Vec<Node>, usize), ParseError> {
     let mut map = HashMap::<String, usize>::new();
     let mut cave = Vec::<Node>::new();
-    let mut start = 0;
-
-    for l in input
-        .replace("valves", "valve")
-        .replace("tunnels", "tunnel")
-        .replace("leads", "lead")
-        .lines()
-    {
-        let mut x = String::new();
-        let mut f = 0;
-        let mut e = String::new();
-
-        if sscanf!(
-            l,
-            "Valve {string} has flow rate={u32}; tunnel lead to valve {string}",
-            x,
-            f,
-            e
-        )
-        .is_ok()
-        {
-            let from = if let Some(idx) = map.get(&x) {
-                if let Some(n) = cave.get_mut(*idx) {
-                    n.flow = f;
-                }
+    let mut start = None;
+
+    for (n, l) in lines(input) {
+        let (x, rest) = l
+            .strip_prefix("Valve ")
+            .and_then(|s| s.split_once(" has flow rate="))
+            .ok_or_else(|| ParseError::line(n, l, "expected `Valve XX has flow rate=..`"))?;
+        let (f, e) = rest
+            .split_once("; tunnels lead to valves ")
+            .or_else(|| rest.split_once("; tunnel leads to valve "))
+            .ok_or_else(|| ParseError::token(n, l, rest, "expected the tunnels"))?;
+        let f = number(n, l, f)?;
+
+        let from = if let Some(idx) = map.get(x) {
+            if let Some(n) = cave.get_mut(*idx) {
+                n.flow = f;
+            }
+            *idx
+        } else {
+            let idx = cave.len();
+            cave.push(Node {
+                name: x.to_owned(),
+                flow: f,
+                neighbors: Vec::new(),
+            });
+
+            map.insert(x.to_owned(), idx);
+            idx
+        };
+
+        if x == "AA" {
+            start = Some(from);
+        }
+
+        for to in e.split(", ") {
+            let to = if let Some(idx) = map.get(to) {
                 *idx
             } else {
                 let idx = cave.len();
                 cave.push(Node {
-                    flow: f,
-                    nonzero_idx: 0,
+                    name: to.to_owned(),
+                    flow: 0,
                     neighbors: Vec::new(),
                 });
 
-                map.insert(x.clone(), idx);
+                map.insert(to.to_owned(), idx);
                 idx
             };
-
-            if x == "AA" {
-                start = from;
+            if let Some(n) = cave.get_mut(from) {
+                n.neighbors.push(to);
             }
+        }
+    }
+
+    let start = start
+        .ok_or_else(|| ParseError::end(input.lines().count() + 1, "", "couldn't find valve AA"))?;
+
+    Ok((cave, start))
+}
+
+// the cave reduced to AA and the valves worth opening
+struct Valves {
+    // index into the cave, AA first
+    rooms: Vec<usize>,
+    flow: Vec<u32>,
+    // minutes to walk between two valves
+    dist: Vec<Vec<u32>>,
+}
+
+impl Valves {
+    fn compress(cave: &[Node], start: usize) -> Self {
+        let n = cave.len();
 
-            for to in e.replace(',', "").split(' ') {
-                let to = if let Some(idx) = map.get(to) {
-                    *idx
-                } else {
-                    let idx = cave.len();
-                    cave.push(Node {
-                        flow: 0,
-                        nonzero_idx: 0,
-                        neighbors: Vec::new(),
-                    });
-
-                    map.insert(to.to_owned(), idx);
-                    idx
-                };
-                if let Some(n) = cave.get_mut(from) {
-                    n.neighbors.push(to);
+        // Floyd-Warshall over the tunnels
+        let mut dist = vec![vec![u32::MAX; n]; n];
+        for (i, room) in cave.iter().enumerate() {
+            dist[i][i] = 0;
+            for &j in room.neighbors.iter() {
+                dist[i][j] = 1;
+            }
+        }
+        for k in 0..n {
+            for i in 0..n {
+                for j in 0..n {
+                    let d = dist[i][k].saturating_add(dist[k][j]);
+                    if d < dist[i][j] {
+                        dist[i][j] = d;
+                    }
                 }
             }
-        } else {
-            unreachable!("{:?}", l)
         }
-    }
 
-    let mut k = 0;
-    for room in cave.iter_mut() {
-        if room.flow > 0 {
-            room.nonzero_idx = k;
-            k += 1
+        // valves that can't be reached from AA are never worth opening
+        let worth = |i: usize| i != start && cave[i].flow > 0 && dist[start][i] < u32::MAX;
+        let rooms = std::iter::once(start)
+            .chain((0..n).filter(|&i| worth(i)))
+            .collect_vec();
+        Valves {
+            flow: rooms.iter().map(|&i| cave[i].flow).collect(),
+            dist: rooms
+                .iter()
+                .map(|&i| rooms.iter().map(|&j| dist[i][j]).collect())
+                .collect(),
+            rooms,
         }
     }
+}
 
-    (cave, start)
+#[derive(Clone)]
+struct Actor {
+    at: usize,
+    left: u32,
+    opened: Vec<usize>,
 }
 
-fn maxflow(
-    minute: usize,
-    location: usize,
-    opened: usize,
-    dp: &mut [Vec<Vec<i32>>],
-    cave: &[Node],
-) -> i32 {
-    let minutes = dp.len();
+// branch and bound over the order in which the actors open the valves
+struct Search<'a> {
+    valves: &'a Valves,
+    // opened or not worth opening
+    taken: BitSet,
+    best: u32,
+    plan: Vec<Vec<usize>>,
+}
 
-    if minute == minutes {
-        return 0;
+impl<'a> Search<'a> {
+    fn new(valves: &'a Valves, actors: usize) -> Self {
+        Search {
+            valves,
+            taken: (0..valves.rooms.len())
+                .filter(|&v| valves.flow[v] == 0)
+                .collect(),
+            best: 0,
+            plan: vec![vec![]; actors],
+        }
     }
 
-    if dp[minute][location][opened] != -1 {
-        return dp[minute][location][opened];
+    // walks the shortest tunnels between the valves of the plan
+    fn schedule<'c>(&self, (cave, start): &'c Parsed, minutes: usize) -> Schedule<'c> {
+        let actors = self
+            .plan
+            .iter()
+            .map(|opened| {
+                let mut location = *start;
+                let mut actions = vec![];
+                for &v in opened {
+                    let room = self.valves.rooms[v];
+                    let path = search::bfs(
+                        location,
+                        |&r| cave[r].neighbors.iter().copied(),
+                        |&r| r == room,
+                    )
+                    .unwrap();
+                    actions.extend(path.nodes[1..].iter().map(|&r| Action::Move(r)));
+                    actions.push(Action::Open(room));
+                    location = room;
+                }
+                actions.resize(minutes, Action::Idle);
+                actions
+            })
+            .collect();
+
+        Schedule { cave, actors }
     }
 
-    let room = &cave[location];
+    // every valve still closed opened as early as it could possibly be: an
+    // actor needs to reach the closest one first and then at least the
+    // shortest distance between two of them per valve, tunnels are one way
+    // so a valve may still be out of reach from another one
+    fn bound(&self, actors: &[Actor]) -> u32 {
+        let dist = &self.valves.dist;
+        let closed = (0..self.valves.rooms.len())
+            .filter(|v| !self.taken.contains(*v))
+            .collect_vec();
+
+        let step = closed
+            .iter()
+            .tuple_combinations()
+            .map(|(&u, &v)| dist[u][v].min(dist[v][u]).saturating_add(1))
+            .min()
+            .unwrap_or(u32::MAX);
+
+        let mut slots = vec![];
+        for a in actors {
+            let Some(d) = closed.iter().map(|&v| dist[a.at][v]).min() else {
+                return 0;
+            };
+            // every actor could open all of them on its own
+            let mut left = a.left.saturating_sub(d.saturating_add(1));
+            for _ in 0..closed.len() {
+                if left == 0 {
+                    break;
+                }
+                slots.push(left);
+                left = left.saturating_sub(step);
+            }
+        }
+        slots.sort_unstable_by(|a, b| b.cmp(a));
 
-    // consider moving to neighbor
-    let mut max = 0;
-    for to in room.neighbors.iter() {
-        max = std::cmp::max(maxflow(minute + 1, *to, opened, dp, cave), max);
+        closed
+            .iter()
+            .map(|&v| self.valves.flow[v])
+            .sorted_unstable_by(|a, b| b.cmp(a))
+            .zip(slots)
+            .map(|(flow, left)| flow * left)
+            .sum()
     }
 
-    // if valve not yet opened and has positive flow rate open it
-    if opened & (1 << room.nonzero_idx) == 0 && room.flow > 0 {
-        let open = opened | 1 << room.nonzero_idx;
-        let added_flow = (minutes as i32 - (minute as i32 + 1)) * room.flow as i32;
-        max = std::cmp::max(
-            added_flow + maxflow(minute + 1, location, open, dp, cave),
-            max,
-        );
+    fn explore(&mut self, actors: &mut [Actor], released: u32) {
+        if released > self.best {
+            self.best = released;
+            self.plan = actors.iter().map(|a| a.opened.clone()).collect();
+        }
+
+        // the actor with the most time left moves next
+        let Some(i) = (0..actors.len())
+            .filter(|&i| actors[i].left > 0)
+            .max_by_key(|&i| actors[i].left)
+        else {
+            return;
+        };
+        if released + self.bound(actors) <= self.best {
+            return;
+        }
+
+        let Actor { at, left, .. } = actors[i];
+        let next = (0..self.valves.rooms.len())
+            .filter(|&v| !self.taken.contains(v) && self.valves.dist[at][v] < left - 1)
+            .map(|v| (v, left - self.valves.dist[at][v] - 1))
+            .sorted_unstable_by_key(|&(v, left)| std::cmp::Reverse(self.valves.flow[v] * left))
+            .collect_vec();
+
+        for (v, l) in next {
+            self.taken.insert(v);
+            actors[i].at = v;
+            actors[i].left = l;
+            actors[i].opened.push(v);
+
+            self.explore(actors, released + self.valves.flow[v] * l);
+
+            actors[i].opened.pop();
+            actors[i].at = at;
+            self.taken.remove(v);
+        }
+
+        // this actor stops, leaving the remaining valves to the others
+        actors[i].left = 0;
+        self.explore(actors, released);
+        actors[i].left = left;
     }
+}
 
-    dp[minute][location][opened] = max;
-    max
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Action {
+    Move(usize),
+    Open(usize),
+    Idle,
 }
 
-#[aoc(day16, part1)]
-pub fn solve_part1((cave, start): &Parsed) -> i32 {
-    let n = cave.len();
+/// Minute by minute actions of you and possibly the elephant.
+pub struct Schedule<'a> {
+    cave: &'a [Node],
+    actors: Vec<Vec<Action>>,
+}
 
-    let k = cave.iter().filter(|n| n.flow > 0).count();
-    let mut dp = vec![vec![vec![-1i32; 1 << k]; n]; 30];
+impl Schedule<'_> {
+    fn opened(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        // (minute the valve is open from, valve)
+        self.actors.iter().flat_map(|actions| {
+            actions.iter().enumerate().filter_map(|(m, a)| match a {
+                Action::Open(v) => Some((m + 1, *v)),
+                _ => None,
+            })
+        })
+    }
 
-    maxflow(0, *start, 0, &mut dp, cave)
+    pub fn released(&self) -> i32 {
+        let minutes = self.actors[0].len();
+        self.opened()
+            .map(|(m, v)| (minutes - m) as i32 * self.cave[v].flow as i32)
+            .sum()
+    }
 }
 
-#[aoc(day16, part2)]
-pub fn solve_part2((cave, start): &Parsed) -> i32 {
-    let n = cave.len();
-    let k = cave.iter().filter(|n| n.flow > 0).count();
+impl Display for Schedule<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let elephants = self.actors.len() - 1;
+        let who = |i: usize| match i {
+            0 => ("You".to_owned(), "move", "open"),
+            _ if elephants == 1 => ("The elephant".to_owned(), "moves", "opens"),
+            _ => (format!("Elephant {i}"), "moves", "opens"),
+        };
+
+        for minute in 0..self.actors[0].len() {
+            if minute > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "== Minute {} ==", minute + 1)?;
+
+            let open = self
+                .opened()
+                .filter(|&(m, _)| m <= minute)
+                .map(|(_, v)| &self.cave[v])
+                .sorted_by_key(|n| &n.name)
+                .collect_vec();
+            let pressure: u32 = open.iter().map(|n| n.flow).sum();
+            let names = open.iter().map(|n| n.name.as_str()).collect_vec();
+            match names.as_slice() {
+                [] => writeln!(f, "No valves are open.")?,
+                [a] => writeln!(f, "Valve {a} is open, releasing {pressure} pressure.")?,
+                [a, b] => writeln!(
+                    f,
+                    "Valves {a} and {b} are open, releasing {pressure} pressure."
+                )?,
+                [rest @ .., last] => writeln!(
+                    f,
+                    "Valves {}, and {last} are open, releasing {pressure} pressure.",
+                    rest.join(", ")
+                )?,
+            }
 
-    let mut dp = vec![vec![vec![-1i32; 1 << k]; n]; 26];
+            for (i, actions) in self.actors.iter().enumerate() {
+                let (who, moves, opens) = who(i);
+                match actions[minute] {
+                    Action::Move(to) => {
+                        writeln!(f, "{who} {moves} to valve {}.", self.cave[to].name)?
+                    }
+                    Action::Open(v) => writeln!(f, "{who} {opens} valve {}.", self.cave[v].name)?,
+                    Action::Idle => (),
+                }
+            }
+        }
+        Ok(())
+    }
+}
 
-    let mut max = 0;
+/// Best plan for you and `actors - 1` elephants opening valves for `minutes`.
+pub fn best_schedule(parsed: &Parsed, minutes: usize, actors: usize) -> Schedule<'_> {
+    assert!(actors > 0, "someone has to open the valves");
+    let valves = Valves::compress(&parsed.0, parsed.1);
+    let mut team = vec![
+        Actor {
+            at: 0,
+            left: minutes as u32,
+            opened: vec![],
+        };
+        actors
+    ];
 
-    // me and elephant have to open disjoint valves so find the maximum by considering all possible disjoint pairs
-    // due to symmetry can skip one half
-    for opened in 0..1 << (k - 1) {
-        let eopened = !opened & ((1 << k) - 1);
+    let mut search = Search::new(&valves, actors);
+    search.explore(&mut team, 0);
+    search.schedule(parsed, minutes)
+}
 
-        let me = maxflow(0, *start, eopened, &mut dp, cave);
-        let elephant = maxflow(0, *start, opened, &mut dp, cave);
+pub fn schedule_part1(parsed: &Parsed) -> Schedule<'_> {
+    best_schedule(parsed, 30, 1)
+}
 
-        max = std::cmp::max(me + elephant, max)
-    }
-    max
+pub fn schedule_part2(parsed: &Parsed) -> Schedule<'_> {
+    best_schedule(parsed, 26, 2)
+}
+
+#[aoc(day16, part1)]
+pub fn solve_part1(parsed: &Parsed) -> i32 {
+    schedule_part1(parsed).released()
+}
+
+#[aoc(day16, part2)]
+pub fn solve_part2(parsed: &Parsed) -> i32 {
+    schedule_part2(parsed).released()
 }
 
 #[cfg(test)]
@@ -173,15 +404,264 @@ Valve HH has flow rate=22; tunnel leads to valve GG
 Valve II has flow rate=0; tunnels lead to valves AA, JJ
 Valve JJ has flow rate=21; tunnel leads to valve II";
 
+    #[test]
+    fn check_malformed() {
+        let err = super::parse(&EXAMPLE.replace("rate=13", "rate=x"))
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 24: expected a number, found `x`"
+        );
+
+        let err = super::parse(&EXAMPLE.replace("Valve AA", "Valve AB"))
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "line 11, column 1: couldn't find valve AA");
+    }
+
     #[test]
     fn check_part1() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part1(&generated), 1651);
     }
 
     #[test]
     fn check_part2() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part2(&generated), 1707);
     }
+
+    #[test]
+    fn check_schedule() {
+        use super::Action;
+        use itertools::Itertools;
+
+        let generated = super::parse(EXAMPLE).unwrap();
+        let (cave, start) = &generated;
+        for (schedule, minutes, released) in [
+            (super::schedule_part1(&generated), 30, 1651),
+            (super::schedule_part2(&generated), 26, 1707),
+        ] {
+            assert_eq!(schedule.released(), released);
+
+            // every actor walks through tunnels and opens each valve at most once
+            let mut opened = vec![];
+            for actions in &schedule.actors {
+                assert_eq!(actions.len(), minutes);
+                let mut at = *start;
+                for action in actions {
+                    match *action {
+                        Action::Move(to) => {
+                            assert!(cave[at].neighbors.contains(&to));
+                            at = to;
+                        }
+                        Action::Open(v) => {
+                            assert_eq!(v, at);
+                            opened.push(v);
+                        }
+                        Action::Idle => (),
+                    }
+                }
+            }
+            assert!(opened.iter().all_unique());
+
+            let text = schedule.to_string();
+            assert!(text.starts_with("== Minute 1 ==\nNo valves are open.\n"));
+            assert!(text.ends_with(&format!(
+                "== Minute {minutes} ==
+Valves BB, CC, DD, EE, HH, and JJ are open, releasing 81 pressure.
+"
+            )));
+        }
+    }
+
+    #[test]
+    fn check_unreachable() {
+        // ZZ only has a tunnel out and KK only one back to itself, so KK is
+        // left for whoever is done first
+        let input = EXAMPLE.replace("valves DD, II, BB", "valves DD, II, BB, KK")
+            + "\nValve ZZ has flow rate=50; tunnel leads to valve AA"
+            + "\nValve KK has flow rate=1; tunnel leads to valve KK";
+        let generated = super::parse(&input).unwrap();
+        assert_eq!(super::solve_part1(&generated), 1651 + 2);
+        assert_eq!(super::solve_part2(&generated), 1707 + 13);
+    }
+
+    // the most pressure `actors` actors can release, trying every order of
+    // opening the valves along the shortest tunnels
+    fn exhaustive(input: &str, minutes: u32, actors: usize) -> u32 {
+        let (cave, start) = super::parse(input).unwrap();
+        // AA and then the valves with some flow
+        let nodes = std::iter::once(start)
+            .chain((0..cave.len()).filter(|&i| cave[i].flow > 0))
+            .collect::<Vec<_>>();
+        let path = |from: usize, to: usize| {
+            let mut seen = vec![from];
+            let mut queue = std::collections::VecDeque::from([(from, 0)]);
+            while let Some((r, d)) = queue.pop_front() {
+                if r == to {
+                    return Some(d);
+                }
+                for &n in &cave[r].neighbors {
+                    if !seen.contains(&n) {
+                        seen.push(n);
+                        queue.push_back((n, d + 1));
+                    }
+                }
+            }
+            None
+        };
+        let dist = nodes
+            .iter()
+            .map(|&i| nodes.iter().map(|&j| path(i, j)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let flow = nodes.iter().map(|&i| cave[i].flow).collect::<Vec<_>>();
+
+        // the most a single actor releases opening exactly the valves in `mask`
+        fn walk(
+            at: usize,
+            left: u32,
+            mask: usize,
+            released: u32,
+            cave: (&[u32], &[Vec<Option<u32>>]),
+            best: &mut [u32],
+        ) {
+            let (flow, dist) = cave;
+            best[mask] = best[mask].max(released);
+            for v in 1..flow.len() {
+                match dist[at][v] {
+                    Some(d) if mask & 1 << (v - 1) == 0 && d + 1 < left => {
+                        let left = left - d - 1;
+                        let released = released + flow[v] * left;
+                        walk(v, left, mask | 1 << (v - 1), released, cave, best);
+                    }
+                    _ => (),
+                }
+            }
+        }
+        let mut alone = vec![0; 1 << (nodes.len() - 1)];
+        walk(0, minutes, 0, 0, (&flow, &dist), &mut alone);
+
+        let mut team = alone.clone();
+        for _ in 1..actors {
+            let mut more = team.clone();
+            for (a, &x) in team.iter().enumerate() {
+                for (b, &y) in alone.iter().enumerate() {
+                    if a & b == 0 {
+                        more[a | b] = more[a | b].max(x + y);
+                    }
+                }
+            }
+            team = more;
+        }
+        team.into_iter().max().unwrap()
+    }
+
+    // a cave of `rooms` rooms with random flows and tunnels, AA first
+    fn random_cave(seed: &mut u64, rooms: usize, directed: bool) -> String {
+        let mut next = |n: u64| {
+            *seed ^= *seed << 13;
+            *seed ^= *seed >> 7;
+            *seed ^= *seed << 17;
+            (*seed >> 32) % n
+        };
+        let name = |i: usize| format!("{}{}", (b'A' + i as u8) as char, (b'A' + i as u8) as char);
+
+        let mut tunnels = vec![vec![]; rooms];
+        for (i, t) in tunnels.iter_mut().enumerate() {
+            // a tunnel to the next room keeps AA connected to most of the cave
+            t.push((i + 1) % rooms);
+        }
+        for _ in 0..rooms {
+            let (a, b) = (next(rooms as u64) as usize, next(rooms as u64) as usize);
+            if a != b && !tunnels[a].contains(&b) {
+                tunnels[a].push(b);
+                if !directed && !tunnels[b].contains(&a) {
+                    tunnels[b].push(a);
+                }
+            }
+        }
+
+        let mut input = vec![];
+        for (i, t) in tunnels.iter().enumerate() {
+            let flow = if i == 0 || next(3) == 0 {
+                0
+            } else {
+                next(25) + 1
+            };
+            let t = t.iter().map(|&j| name(j)).collect::<Vec<_>>().join(", ");
+            input.push(format!(
+                "Valve {} has flow rate={flow}; tunnels lead to valves {t}",
+                name(i)
+            ));
+        }
+        input.join("\n")
+    }
+
+    #[test]
+    fn check_exhaustive() {
+        let mut seed: u64 = 0x2022_1216;
+        for i in 0..200 {
+            let input = random_cave(&mut seed, 8, i % 2 == 0);
+            let generated = super::parse(&input).unwrap();
+            assert_eq!(
+                super::solve_part1(&generated) as u32,
+                exhaustive(&input, 30, 1),
+                "{input}"
+            );
+            assert_eq!(
+                super::solve_part2(&generated) as u32,
+                exhaustive(&input, 26, 2),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn check_many_valves() {
+        // AA in the middle of a star of 70 valves, the last one is the best
+        let valves = (0..70).map(|i| format!("V{i:02}")).collect::<Vec<_>>();
+        let mut input = format!(
+            "Valve AA has flow rate=0; tunnels lead to valves {}",
+            valves.join(", ")
+        );
+        for (i, v) in valves.iter().enumerate() {
+            let flow = if i == 69 { 100 } else { 1 };
+            input += &format!("\nValve {v} has flow rate={flow}; tunnel leads to valve AA");
+        }
+
+        let generated = super::parse(&input).unwrap();
+        // V69 after 2 minutes, then another valve every 3 minutes
+        assert_eq!(
+            super::solve_part1(&generated),
+            28 * 100 + (1..=25).step_by(3).sum::<i32>()
+        );
+    }
+
+    #[test]
+    fn check_actors() {
+        let generated = super::parse(EXAMPLE).unwrap();
+        assert_eq!(super::best_schedule(&generated, 30, 1).released(), 1651);
+        assert_eq!(super::best_schedule(&generated, 26, 2).released(), 1707);
+
+        let schedule = super::best_schedule(&generated, 22, 3);
+        assert_eq!(schedule.released(), 1470);
+        assert!(schedule.to_string().contains("Elephant 2 moves to valve"));
+
+        assert_eq!(super::best_schedule(&generated, 0, 2).released(), 0);
+
+        // the actors run out of time at different minutes, 1713 is what
+        // trying every split and order of the valves gives
+        let cave = "Valve AA has flow rate=0; tunnels lead to valves BB, DD
+Valve BB has flow rate=14; tunnels lead to valves CC, FF, GG
+Valve CC has flow rate=19; tunnels lead to valves DD, FF
+Valve DD has flow rate=20; tunnels lead to valves EE, AA, CC
+Valve EE has flow rate=22; tunnels lead to valves FF
+Valve FF has flow rate=20; tunnels lead to valves GG, BB, CC
+Valve GG has flow rate=7; tunnels lead to valves AA, BB";
+        let generated = super::parse(cave).unwrap();
+        assert_eq!(super::best_schedule(&generated, 20, 3).released(), 1713);
+        assert_eq!(exhaustive(cave, 20, 3), 1713);
+    }
 }
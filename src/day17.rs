@@ -1,225 +1,601 @@
-use std::collections::{HashMap, HashSet};
-
-type Coord = (i64, i64);
-
-// for convenience all length 5
-// left edge at 0, 0 and aligned with bottom where possible
-// only special case: plus there we have to add one to the height
-const HORIZONTAL: [Coord; 5] = [(0, 0), (1, 0), (2, 0), (3, 0), (0, 0)];
-const PLUS: [Coord; 5] = [(0, 0), (1, 0), (2, 0), (1, -1), (1, 1)];
-const CORNER: [Coord; 5] = [(0, 0), (1, 0), (2, 0), (2, 1), (2, 2)];
-const VERTICAL: [Coord; 5] = [(0, 0), (0, 1), (0, 2), (0, 3), (0, 0)];
-const SQUARE: [Coord; 5] = [(0, 0), (0, 1), (1, 0), (1, 1), (0, 0)];
-
-fn get_rock(idx: usize) -> &'static [Coord; 5] {
-    match idx {
-        0 => &HORIZONTAL,
-        1 => &PLUS,
-        2 => &CORNER,
-        3 => &VERTICAL,
-        4 => &SQUARE,
-        _ => unreachable!(),
-    }
-}
+use std::collections::VecDeque;
 
-type Fallen = HashSet<(i64, i64)>;
+use itertools::Itertools;
 
-#[aoc_generator(day17)]
-pub fn parse(input: &str) -> String {
-    input.to_owned()
-}
+use crate::{
+    cycle,
+    error::{lines, ParseError},
+};
 
-fn affected(position: &Coord, rock: &[Coord; 5], fallen: &mut Fallen, dx: i64) -> bool {
-    for part in rock.iter().take(5) {
-        let x = part.0 + position.0 + dx;
-        let y = part.1 + position.1;
+type Row = u64;
 
-        if x < 0 || x == 7 || fallen.contains(&(x, y)) {
-            return false;
-        }
-    }
-    true
-}
+// the rocks of the puzzle in the order they fall
+const PUZZLE_ROCKS: &str = "####
 
-fn rest(position: &Coord, rock: &[Coord; 5], fallen: &mut Fallen) -> bool {
-    for part in rock.iter().take(5) {
-        let x = part.0 + position.0;
-        let y = part.1 + position.1;
+.#.
+###
+.#.
 
-        if fallen.contains(&(x, y - 1)) || y == 0 {
-            return true;
-        }
+..#
+..#
+###
+
+#
+#
+#
+#
+
+##
+##";
+
+// rows below the top still open to rocks before giving up on finding a cycle
+const MAX_OPEN: usize = 1000;
+
+/// Rock shapes falling in turn into a chamber `width` units wide.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    // one bitmask per row from the bottom up, bit x set for the column x,
+    // already two units away from the left wall
+    rocks: Vec<Vec<Row>>,
+    width: usize,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules::parse(PUZZLE_ROCKS, 7).unwrap()
     }
-    false
 }
 
-fn fall(
-    rock_idx: usize,
-    wind_idx: &mut usize,
-    winds: &[u8],
-    height: &mut i64,
-    fallen: &mut Fallen,
-) {
-    let rock = get_rock(rock_idx);
-
-    let mut position = (2, *height + 3);
-    if rock_idx == 1 {
-        position.1 += 1;
-    }
-
-    loop {
-        let wind = winds[*wind_idx];
-        *wind_idx = (*wind_idx + 1usize) % winds.len();
-
-        position.0 += match wind {
-            b'>' => {
-                if affected(&position, rock, fallen, 1) {
-                    1
-                } else {
-                    0
+impl Rules {
+    /// Rocks drawn with `#` and `.` like in the puzzle, separated by blank
+    /// lines.
+    pub fn parse(shapes: &str, width: usize) -> Result<Rules, ParseError> {
+        let eof = shapes.lines().count() + 1;
+        if !(3..=Row::BITS as usize).contains(&width) {
+            return Err(ParseError::end(
+                eof,
+                "",
+                format!("a chamber {width} wide doesn't fit any rock"),
+            ));
+        }
+
+        let mut rocks = vec![];
+        let mut rock: Vec<(usize, &str, Vec<usize>)> = vec![];
+        for (n, l) in lines(shapes).chain([(eof, "")]) {
+            if !l.is_empty() {
+                if let Some(c) = l.matches(|c| c != '#' && c != '.').next() {
+                    return Err(ParseError::token(n, l, c, "expected a rock # or ."));
                 }
+                rock.push((n, l, l.match_indices('#').map(|(x, _)| x).collect()));
+                continue;
             }
-            b'<' => {
-                if affected(&position, rock, fallen, -1) {
-                    -1
-                } else {
-                    0
-                }
+            if rock.is_empty() {
+                continue;
             }
-            _ => unreachable!(),
-        };
 
-        if rest(&position, rock, fallen) {
-            for i in 0..5 {
-                let rock = get_rock(rock_idx);
-                let part = rock[i];
-                let x = position.0 + part.0;
-                let y = position.1 + part.1;
-
-                if y + 1 > *height {
-                    *height = y + 1
-                };
-
-                fallen.insert((x, y));
+            // only the cells matter, not the padding around them
+            let left = rock.iter().flat_map(|(_, _, xs)| xs).min();
+            let Some(&left) = left else {
+                let (n, l, _) = rock[0];
+                return Err(ParseError::line(n, l, "expected a rock with a #"));
+            };
+            rock.retain(|(_, _, xs)| !xs.is_empty());
+
+            let mut masks = vec![];
+            for (n, l, xs) in rock.drain(..).rev() {
+                if xs.iter().any(|x| x - left + 2 >= width) {
+                    return Err(ParseError::line(
+                        n,
+                        l,
+                        format!("rock doesn't fit a chamber {width} wide"),
+                    ));
+                }
+                masks.push(xs.iter().fold(0, |mask, x| mask | 1 << (x - left + 2)));
             }
-            break;
+            rocks.push(masks);
         }
 
-        position.1 -= 1;
+        if rocks.is_empty() {
+            return Err(ParseError::end(eof, "", "expected a rock"));
+        }
+        Ok(Rules { rocks, width })
+    }
+
+    fn full(&self) -> Row {
+        Row::MAX >> (Row::BITS as usize - self.width)
     }
 }
 
-fn _print(height: i64, fallen: &Fallen) {
-    for y in (0..height).rev() {
-        print!("|");
-        for x in 0..7 {
-            if fallen.contains(&(x, y)) {
-                print!("#")
-            } else {
-                print!(".")
-            }
+#[aoc_generator(day17)]
+pub fn parse(input: &str) -> Result<Vec<u8>, ParseError> {
+    for (n, l) in lines(input) {
+        if let Some(c) = l.matches(|c| c != '<' && c != '>').next() {
+            return Err(ParseError::token(n, l, c, "expected a jet < or >"));
         }
-        println!("|");
     }
-    println!("+-------+\n\n")
+    let winds = lines(input).flat_map(|(_, l)| l.bytes()).collect_vec();
+    if winds.is_empty() {
+        let eof = input.lines().count() + 1;
+        return Err(ParseError::end(eof, "", "expected at least one jet"));
+    }
+    Ok(winds)
 }
 
-#[aoc(day17, part1)]
-pub fn solve_part1(winds: &String) -> i64 {
-    let mut fallen = HashSet::new();
-    let winds = winds.as_bytes();
+// y increases upwards from the floor
+struct Chamber<'a> {
+    rules: &'a Rules,
+    winds: &'a [u8],
+    rock_idx: usize,
+    wind_idx: usize,
+    height: i64,
+    // settled cells of the rows from `floor` up to `height`
+    rows: VecDeque<Row>,
+    // everything below is sealed off from the rocks to come
+    floor: i64,
+}
 
-    let mut rock_idx = 0;
-    let mut wind_idx = 0;
+impl<'a> Chamber<'a> {
+    fn new(rules: &'a Rules, winds: &'a [u8]) -> Self {
+        Chamber {
+            rules,
+            winds,
+            rock_idx: 0,
+            wind_idx: 0,
+            height: 0,
+            rows: VecDeque::new(),
+            floor: 0,
+        }
+    }
 
-    let mut height = 0;
+    fn row(&self, y: i64) -> Row {
+        match y {
+            _ if y < self.floor => self.rules.full(),
+            _ => self
+                .rows
+                .get((y - self.floor) as usize)
+                .copied()
+                .unwrap_or(0),
+        }
+    }
 
-    for _ in 0..2022 {
-        fall(rock_idx, &mut wind_idx, winds, &mut height, &mut fallen);
-        rock_idx = (rock_idx + 1) % 5;
-        //print(height, &fallen);
+    fn fits(&self, rock: &[Row], y: i64) -> bool {
+        rock.iter()
+            .enumerate()
+            .all(|(i, part)| part & self.row(y + i as i64) == 0)
     }
 
-    height
-}
+    // returns where the rock came to rest and its rows
+    fn drop_rock(&mut self) -> (i64, Vec<Row>) {
+        let mut rock = self.rules.rocks[self.rock_idx].clone();
+        let wall = 1 << (self.rules.width - 1);
+        let mut y = self.height + 3;
+
+        loop {
+            let wind = self.winds[self.wind_idx];
+            self.wind_idx = (self.wind_idx + 1) % self.winds.len();
+
+            let pushed = match wind {
+                b'>' if rock.iter().all(|part| part & wall == 0) => {
+                    rock.iter().map(|part| part << 1).collect_vec()
+                }
+                b'<' if rock.iter().all(|part| part & 1 == 0) => {
+                    rock.iter().map(|part| part >> 1).collect_vec()
+                }
+                _ => rock.clone(),
+            };
+            if self.fits(&pushed, y) {
+                rock = pushed;
+            }
+
+            if !self.fits(&rock, y - 1) {
+                break;
+            }
+            y -= 1;
+        }
+
+        for (i, part) in rock.iter().enumerate() {
+            let row = (y - self.floor) as usize + i;
+            if row >= self.rows.len() {
+                self.rows.resize(row + 1, 0);
+            }
+            self.rows[row] |= part;
+        }
+        self.height = self.height.max(y + rock.len() as i64);
+        self.rock_idx = (self.rock_idx + 1) % self.rules.rocks.len();
+
+        // no rock gets below the cells still open to them
+        let floor = self.height + 1 - self.surface().len() as i64;
+        if floor > self.floor {
+            self.rows.drain(..(floor - self.floor) as usize);
+            self.floor = floor;
+        }
+        (y, rock)
+    }
+
+    // the next rock, the next jet and what is left open of the tower
+    fn key(&self) -> (usize, usize, Vec<Row>) {
+        (self.rock_idx, self.wind_idx, self.surface())
+    }
+
+    // empty cells a rock could still get to (moving sideways and down from
+    // above the tower) as bitmasks, row by row from the top
+    //
+    // nothing below them can change the future of the tower
+    fn surface(&self) -> Vec<Row> {
+        let full = self.rules.full();
+        let mut rows = vec![full];
+        for y in (0..self.height).rev() {
+            let open = !self.row(y) & full;
+            let mut reach = rows[rows.len() - 1] & open;
+            loop {
+                let spread = (reach | reach << 1 | reach >> 1) & open;
+                if spread == reach {
+                    break;
+                }
+                reach = spread;
+            }
 
-fn relief(height: i64, fallen: &Fallen) -> [u32; 50] {
-    let mut r = [0; 50];
-    for h in 1..=50 {
-        let y = height - h;
-        for x in 0..7 {
-            if fallen.contains(&(x, y)) {
-                r[h as usize - 1] |= 1 << x;
+            if reach == 0 {
+                break;
             }
+            rows.push(reach);
         }
+        rows
     }
-    r
 }
 
-#[aoc(day17, part2)]
-pub fn solve_part2(winds: &String) -> i64 {
-    let mut fallen = HashSet::new();
+/// The chamber keeping every rock (tagged with its shape) for drawing.
+pub struct Tower<'a> {
+    chamber: Chamber<'a>,
+    // from the floor up
+    shapes: Vec<Vec<Option<usize>>>,
+}
 
-    let target = 1_000_000_000_000;
-    //let target = 2022;
+const PALETTE: [[u8; 3]; 5] = [
+    [0x4e, 0x79, 0xa7],
+    [0xf2, 0x8e, 0x2b],
+    [0xe1, 0x57, 0x59],
+    [0x76, 0xb7, 0xb2],
+    [0x59, 0xa1, 0x4f],
+];
+const BACKGROUND: [u8; 3] = [0x1e, 0x1e, 0x1e];
+const FALLING: [u8; 3] = [0xff, 0xff, 0xff];
+
+#[derive(Clone, Copy)]
+enum Cell {
+    Empty,
+    // settled, with the index of its shape
+    Rock(usize),
+    Falling,
+}
 
-    let mut cycles = HashMap::<[u32; 50], (i64, i64)>::new();
+impl<'a> Tower<'a> {
+    pub fn new(rules: &'a Rules, winds: &'a [u8]) -> Self {
+        Tower {
+            chamber: Chamber::new(rules, winds),
+            shapes: vec![],
+        }
+    }
 
-    let winds = winds.as_bytes();
+    pub fn height(&self) -> i64 {
+        self.chamber.height
+    }
 
-    let mut rock_idx = 0;
-    let mut wind_idx = 0;
+    pub fn drop_rock(&mut self) {
+        let shape = self.chamber.rock_idx;
+        let (y, rock) = self.chamber.drop_rock();
 
-    let mut height = 0;
+        let top = y as usize + rock.len();
+        if top > self.shapes.len() {
+            self.shapes
+                .resize(top, vec![None; self.chamber.rules.width]);
+        }
+        for (row, part) in self.shapes[y as usize..].iter_mut().zip(rock) {
+            for (x, cell) in row.iter_mut().enumerate() {
+                if part & 1 << x != 0 {
+                    *cell = Some(shape);
+                }
+            }
+        }
+    }
 
-    let mut rounds_remaining = 0;
-    let mut round_delta = 0;
-    let mut height_delta = 0;
+    // what to draw in every row of the top `rows` (or all of them) from the
+    // top down, the next rock appears above the tower like in the puzzle
+    fn cells(&self, rows: Option<usize>) -> Vec<Vec<Cell>> {
+        let next = &self.chamber.rules.rocks[self.chamber.rock_idx];
+        let top = self.shapes.len() + 3 + next.len();
+
+        let mut cells = self
+            .shapes
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|shape| shape.map_or(Cell::Empty, Cell::Rock))
+                    .collect_vec()
+            })
+            .collect_vec();
+        cells.resize(top, vec![Cell::Empty; self.chamber.rules.width]);
+        for (row, part) in cells[top - next.len()..].iter_mut().zip(next) {
+            for (x, cell) in row.iter_mut().enumerate() {
+                if part & 1 << x != 0 {
+                    *cell = Cell::Falling;
+                }
+            }
+        }
 
-    for i in 1i64.. {
-        fall(rock_idx, &mut wind_idx, winds, &mut height, &mut fallen);
-        let relief = relief(height, &fallen);
+        cells.reverse();
+        cells.truncate(rows.unwrap_or(top));
+        cells
+    }
 
-        rock_idx = (rock_idx + 1) % 5;
+    /// ASCII art like the puzzle, the top `rows` rows or the whole tower down
+    /// to the floor.
+    pub fn ascii(&self, rows: Option<usize>) -> String {
+        let mut art = String::new();
+        for row in self.cells(rows) {
+            let row = row
+                .iter()
+                .map(|cell| match cell {
+                    Cell::Empty => '.',
+                    Cell::Rock(_) => '#',
+                    Cell::Falling => '@',
+                })
+                .collect::<String>();
+            art += &format!("|{row}|\n");
+        }
+        if rows.is_none() {
+            art += &format!("+{}+\n", "-".repeat(self.chamber.rules.width));
+        }
+        art
+    }
 
-        if let Some((k, h)) = cycles.get(&relief) {
-            println!("detected cycle between {k} to {i}, heights: {h} -> {height}");
-            rounds_remaining = target - i;
-            round_delta = i - k;
-            height_delta = height - h;
-            break;
-        } else {
-            cycles.insert(relief, (i, height));
+    /// Binary PPM of the top `rows` rows (or the whole tower) with every rock
+    /// colored by its shape, each cell is `scale` pixels wide.
+    pub fn ppm(&self, rows: Option<usize>, scale: usize) -> Vec<u8> {
+        let cells = self.cells(rows);
+        let mut ppm = format!(
+            "P6\n{} {}\n255\n",
+            self.chamber.rules.width * scale,
+            cells.len() * scale
+        )
+        .into_bytes();
+        for row in &cells {
+            let pixels = row
+                .iter()
+                .flat_map(|cell| {
+                    let color = match cell {
+                        Cell::Empty => BACKGROUND,
+                        Cell::Rock(shape) => PALETTE[shape % PALETTE.len()],
+                        Cell::Falling => FALLING,
+                    };
+                    std::iter::repeat_n(color, scale)
+                })
+                .flatten()
+                .collect_vec();
+            for _ in 0..scale {
+                ppm.extend(&pixels);
+            }
         }
+        ppm
     }
+}
 
-    let num_cycles = rounds_remaining / round_delta;
-    let sim = rounds_remaining % round_delta;
+/// Renders the tower after each of the first `rocks` rocks, e.g. as frames of
+/// an animation.
+pub fn frames<T, F>(rules: &Rules, winds: &[u8], rocks: usize, mut render: F) -> Vec<T>
+where
+    F: FnMut(&Tower) -> T,
+{
+    let mut tower = Tower::new(rules, winds);
+    (0..rocks)
+        .map(|_| {
+            tower.drop_rock();
+            render(&tower)
+        })
+        .collect()
+}
+
+/// Height of the tower after `rocks` rocks.
+///
+/// Panics if the tower keeps a shaft open too deep for any state to repeat,
+/// e.g. when no rock is ever pushed into some column.
+pub fn height(rules: &Rules, winds: &[u8], rocks: u64) -> i64 {
+    let cycle = cycle::detect(
+        &mut Chamber::new(rules, winds),
+        |chamber| {
+            chamber.drop_rock();
+            assert!(
+                chamber.rows.len() < MAX_OPEN,
+                "the tower never seals off, no state can repeat"
+            );
+        },
+        Chamber::key,
+        |chamber| chamber.height,
+    );
+    cycle.extrapolate(rocks)
+}
 
-    // simulate the remaining rounds to reach the target
-    for _ in 0..sim {
-        fall(rock_idx, &mut wind_idx, winds, &mut height, &mut fallen);
-        rock_idx = (rock_idx + 1) % 5;
+#[aoc(day17, part1)]
+pub fn solve_part1(winds: &[u8]) -> i64 {
+    let rules = Rules::default();
+    let mut chamber = Chamber::new(&rules, winds);
+    for _ in 0..2022 {
+        chamber.drop_rock();
     }
 
-    // add the height from the cycles to the result
-    height += num_cycles * height_delta;
-    height
+    chamber.height
+}
+
+#[aoc(day17, part2)]
+pub fn solve_part2(winds: &[u8]) -> i64 {
+    height(&Rules::default(), winds, 1_000_000_000_000)
 }
 
 #[cfg(test)]
 mod tests {
     const EXAMPLE: &str = ">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>";
 
+    #[test]
+    fn check_malformed() {
+        let err = super::parse(">><x").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 1, column 4: expected a jet < or >, found `x`"
+        );
+        let err = super::parse("\n\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 3, column 1: expected at least one jet"
+        );
+    }
+
     #[test]
     fn check_part1() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part1(&generated), 3068);
     }
 
     #[test]
     fn check_part2() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part2(&generated), 1514285714288);
     }
+
+    #[test]
+    fn check_surface() {
+        let generated = super::parse(EXAMPLE).unwrap();
+        let rules = super::Rules::default();
+        let mut chamber = super::Chamber::new(&rules, &generated);
+        assert_eq!(chamber.key(), (0, 0, vec![0b1111111]));
+
+        // the first rock lands flat in the middle of the floor
+        chamber.drop_rock();
+        assert_eq!(chamber.key(), (1, 4, vec![0b1111111, 0b1000011]));
+    }
+
+    #[test]
+    fn check_pruning() {
+        let generated = super::parse(EXAMPLE).unwrap();
+        let rules = super::Rules::default();
+        let mut chamber = super::Chamber::new(&rules, &generated);
+        for _ in 0..2022 {
+            chamber.drop_rock();
+        }
+        assert_eq!(chamber.height, 3068);
+        assert!(chamber.rows.len() < 100, "{} rows kept", chamber.rows.len());
+        assert_eq!(chamber.floor + chamber.rows.len() as i64, chamber.height);
+    }
+
+    #[test]
+    fn check_render() {
+        let generated = super::parse(EXAMPLE).unwrap();
+        let frames = super::frames(&super::Rules::default(), &generated, 2, |tower| {
+            tower.ascii(None)
+        });
+        assert_eq!(
+            frames[0],
+            "|...@...|
+|..@@@..|
+|...@...|
+|.......|
+|.......|
+|.......|
+|..####.|
++-------+
+"
+        );
+        assert_eq!(
+            frames[1],
+            "|....@..|
+|....@..|
+|..@@@..|
+|.......|
+|.......|
+|.......|
+|...#...|
+|..###..|
+|...#...|
+|..####.|
++-------+
+"
+        );
+
+        let rules = super::Rules::default();
+        let mut tower = super::Tower::new(&rules, &generated);
+        for _ in 0..10 {
+            tower.drop_rock();
+        }
+        assert_eq!(tower.height(), 17);
+        assert_eq!(tower.ascii(Some(3)).lines().count(), 3);
+
+        let ppm = tower.ppm(Some(20), 2);
+        let header = b"P6\n14 40\n255\n";
+        assert!(ppm.starts_with(header));
+        assert_eq!(ppm.len(), header.len() + 14 * 40 * 3);
+    }
+
+    #[test]
+    fn check_rules() {
+        let rules = super::Rules::default();
+        assert_eq!(rules.rocks.len(), 5);
+        assert_eq!(rules.rocks[1], [0b0001000, 0b0011100, 0b0001000]);
+        assert_eq!(rules.rocks[2], [0b0011100, 0b0010000, 0b0010000]);
+
+        // padding around the cells is ignored
+        let padded = super::Rules::parse("\n..#.\n\n\n.##.\n....\n", 5).unwrap();
+        assert_eq!(padded.rocks, [vec![0b100], vec![0b1100]]);
+
+        let err = super::Rules::parse("###\n\n.#x", 7).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 3, column 3: expected a rock # or ., found `x`"
+        );
+        let err = super::Rules::parse("##\n\n####", 5).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 3, column 1: rock doesn't fit a chamber 5 wide, found `####`"
+        );
+        let err = super::Rules::parse("#\n\n...\n", 5).unwrap_err();
+        assert_eq!(err.line, 3);
+        assert!(super::Rules::parse("", 7).is_err());
+        assert!(super::Rules::parse("#", 2).is_err());
+    }
+
+    #[test]
+    fn check_variants() {
+        // the extrapolated height has to match dropping every rock
+        let mirrored = EXAMPLE
+            .replace('<', ".")
+            .replace('>', "<")
+            .replace('.', ">");
+        // no two rows in a row ever close every column, only diagonals do,
+        // and it takes thousands of rows for the state to repeat
+        let diagonal = ">><<<".repeat(1000) + ">";
+        let variants = [
+            ("#", 3),
+            ("##\n\n#\n#", 4),
+            ("#.#\n###\n\n#\n\n.#\n##", 6),
+            (super::PUZZLE_ROCKS, 7),
+            ("####\n\n#..\n###", 7),
+        ];
+        let cases = variants
+            .into_iter()
+            .flat_map(|(shapes, width)| [(shapes, width, EXAMPLE), (shapes, width, &mirrored)])
+            .chain([("##", 4, diagonal.as_str())]);
+        for (shapes, width, winds) in cases {
+            let rules = super::Rules::parse(shapes, width).unwrap();
+            let winds = winds.as_bytes();
+            let mut chamber = super::Chamber::new(&rules, winds);
+            for rocks in 0..3000 {
+                if rocks % 1000 == 7 {
+                    assert_eq!(
+                        super::height(&rules, winds, rocks),
+                        chamber.height,
+                        "{rocks} rocks in a chamber {width} wide"
+                    );
+                }
+                chamber.drop_rock();
+            }
+        }
+    }
 }
@@ -1,127 +1,79 @@
-use std::{
-    cmp::Ordering,
-    collections::HashSet,
-    ops::{Add, Sub},
-};
+use std::collections::HashSet;
 
 use itertools::Itertools;
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
-pub struct Cube {
-    x: i32,
-    y: i32,
-    z: i32,
-}
+use crate::{
+    error::{lines, number, ParseError},
+    search,
+    vec::Vec3,
+};
+
+type Cube = Vec3;
 
 const ADJACENT: [Cube; 6] = [
-    Cube { x: -1, y: 0, z: 0 },
-    Cube { x: 1, y: 0, z: 0 },
-    Cube { x: 0, y: -1, z: 0 },
-    Cube { x: 0, y: 1, z: 0 },
-    Cube { x: 0, y: 0, z: -1 },
-    Cube { x: 0, y: 0, z: 1 },
+    Vec3 { x: -1, y: 0, z: 0 },
+    Vec3::X,
+    Vec3 { x: 0, y: -1, z: 0 },
+    Vec3::Y,
+    Vec3 { x: 0, y: 0, z: -1 },
+    Vec3::Z,
 ];
 
-impl Cube {
-    fn sides(&self) -> impl Iterator<Item = Cube> + '_ {
-        ADJACENT.iter().map(|adj| *self + *adj)
-    }
-
-    // 12 sides in total
-    fn reachable_sides<'a>(
-        &'a self,
-        outside: &'a Cube,
-    ) -> impl Iterator<Item = (Cube, Cube, Option<Cube>)> + '_ {
-        let delta = *outside - *self;
-
-        // consider the sides adjacent to the current cube (4 sides)
-        // !! this move is only valid when adj + delta is not a cube aswell !!
-        let opposite = *self - *outside;
-        let one = self.sides().filter_map(move |adj| {
-            if adj != *outside && adj != opposite {
-                Some((*self, adj, Some(adj + delta)))
-            } else {
-                None
-            }
-        });
-
-        // consider the sides in the same plane as the given side (4 sides)
-        let two = self.sides().filter_map(move |adj| {
-            let outoutside = adj + delta;
-            if adj != *outside && outoutside != *self {
-                Some((adj, adj + delta, None))
-            } else {
-                None
-            }
-        });
-
-        // consider the sides adjacent to the outside cube (4 sides)
-        let outoutside = *outside + delta;
-        let three = outside.sides().filter_map(move |adj| {
-            if adj != outoutside && adj != *self {
-                Some((adj, *outside, None))
-            } else {
-                None
-            }
-        });
-
-        one.chain(two).chain(three)
-    }
+fn sides(cube: Cube) -> impl Iterator<Item = Cube> {
+    ADJACENT.iter().map(move |adj| cube + *adj)
 }
 
-impl Add for Cube {
-    type Output = Self;
-
-    fn add(self, rhs: Self) -> Self::Output {
-        Cube {
-            x: self.x + rhs.x,
-            y: self.y + rhs.y,
-            z: self.z + rhs.z,
+// 12 sides in total
+fn reachable_sides(cube: Cube, outside: Cube) -> impl Iterator<Item = (Cube, Cube, Option<Cube>)> {
+    let delta = outside - cube;
+
+    // consider the sides adjacent to the current cube (4 sides)
+    // !! this move is only valid when adj + delta is not a cube aswell !!
+    let opposite = cube - outside;
+    let one = sides(cube).filter_map(move |adj| {
+        if adj != outside && adj != opposite {
+            Some((cube, adj, Some(adj + delta)))
+        } else {
+            None
         }
-    }
-}
-
-impl Sub for Cube {
-    type Output = Self;
-
-    fn sub(self, rhs: Self) -> Self::Output {
-        Cube {
-            x: self.x - rhs.x,
-            y: self.y - rhs.y,
-            z: self.z - rhs.z,
+    });
+
+    // consider the sides in the same plane as the given side (4 sides)
+    let two = sides(cube).filter_map(move |adj| {
+        let outoutside = adj + delta;
+        if adj != outside && outoutside != cube {
+            Some((adj, adj + delta, None))
+        } else {
+            None
         }
-    }
-}
-
-impl Ord for Cube {
-    fn cmp(&self, other: &Self) -> Ordering {
-        match self.x.cmp(&other.x) {
-            Ordering::Equal => match self.y.cmp(&other.y) {
-                Ordering::Equal => self.z.cmp(&other.z),
-                o => o,
-            },
-            o => o,
+    });
+
+    // consider the sides adjacent to the outside cube (4 sides)
+    let outoutside = outside + delta;
+    let three = sides(outside).filter_map(move |adj| {
+        if adj != outoutside && adj != cube {
+            Some((adj, outside, None))
+        } else {
+            None
         }
-    }
-}
+    });
 
-impl PartialOrd for Cube {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
+    one.chain(two).chain(three)
 }
 
 #[aoc_generator(day18)]
-pub fn parse(input: &str) -> HashSet<Cube> {
-    input
-        .lines()
-        .map(|l| {
-            l.split(',')
-                .filter_map(|x| x.parse::<i32>().ok())
-                .tuples()
-                .next()
-                .map(|(x, y, z)| Cube { x, y, z })
-                .unwrap()
+pub fn parse(input: &str) -> Result<HashSet<Cube>, ParseError> {
+    lines(input)
+        .map(|(n, l)| {
+            let (x, y, z) = l
+                .split(',')
+                .collect_tuple()
+                .ok_or_else(|| ParseError::line(n, l, "expected a cube x,y,z"))?;
+            Ok(Vec3::new(
+                number(n, l, x)?,
+                number(n, l, y)?,
+                number(n, l, z)?,
+            ))
         })
         .collect()
 }
@@ -141,32 +93,28 @@ pub fn solve_part1(cubes: &HashSet<Cube>) -> usize {
 
 #[aoc(day18, part2)]
 pub fn solve_part2(cubes: &HashSet<Cube>) -> usize {
-    let mut visited = HashSet::<(Cube, Cube)>::new();
-
-    let mut stack = Vec::<(Cube, Cube)>::new();
+    // walk the outer surface side by side, a side is a cube and its empty neighbor
     let min = cubes.iter().min().unwrap();
-    stack.push((*min, Cube { x: -1, y: 0, z: 0 } + *min));
-
-    while let Some((a, b)) = stack.pop() {
-        visited.insert((a, b));
-        for (c, d, e) in a.reachable_sides(&b) {
-            if cubes.contains(&c) && !cubes.contains(&d) && !visited.contains(&(c, d)) {
-                if let Some(e) = e {
-                    if cubes.contains(&e) {
-                        continue;
-                    }
+    let visited = search::reachable((*min, *min - Vec3::X), |&(a, b)| {
+        reachable_sides(a, b).filter_map(|(c, d, e)| {
+            if !cubes.contains(&c) || cubes.contains(&d) {
+                return None;
+            }
+            if let Some(e) = e {
+                if cubes.contains(&e) {
+                    return None;
                 }
-                stack.push((c, d))
             }
-        }
-    }
+            Some((c, d))
+        })
+    });
 
     visited.len()
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Cube;
+    use crate::vec::Vec3;
 
     const EXAMPLE: &str = "2,2,2
 1,2,2
@@ -184,20 +132,20 @@ mod tests {
 
     #[test]
     fn check_part1() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part1(&generated), 64);
     }
 
     #[test]
     fn check_part2() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part2(&generated), 58);
     }
 
     #[test]
     fn check_reachable() {
-        let a = Cube { x: 0, y: 0, z: 0 };
-        let b = Cube { x: 0, y: 1, z: 0 };
-        a.reachable_sides(&b).for_each(|c| println!("{:?}", c));
+        let a = Vec3::ZERO;
+        let b = Vec3::Y;
+        super::reachable_sides(a, b).for_each(|c| println!("{:?}", c));
     }
 }
@@ -1,226 +1,301 @@
-use std::collections::HashSet;
+use std::fmt::Display;
 
 use itertools::Itertools;
 use rayon::prelude::*;
 
-type Mem = u8;
+use crate::error::{lines, number, ParseError};
 
+type Mem = u32;
+
+/// Robot recipes, the robot `i` collects the resource `i`.
 #[derive(Debug)]
 pub struct Blueprint {
-    ore: Mem,
-    clay: Mem,
-    obsidian: (Mem, Mem),
-    geode: (Mem, Mem),
+    names: Vec<String>,
+    // costs[i][j] of resource j to build robot i
+    costs: Vec<Vec<Mem>>,
+    // the factory starts with one ore robot and wants geodes
+    ore: usize,
+    geode: usize,
+}
+
+impl Blueprint {
+    pub fn resource(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| n == name)
+    }
 }
 
 #[aoc_generator(day19)]
-pub fn parse(input: &str) -> Vec<Blueprint> {
-    input
-        .lines()
-        .map(|l| {
-            let mut split = l.split(' ').filter_map(|x| x.parse::<Mem>().ok());
-            Blueprint {
-                ore: split.next().unwrap(),
-                clay: split.next().unwrap(),
-                obsidian: split.next_tuple().unwrap(),
-                geode: split.next_tuple().unwrap(),
+pub fn parse(input: &str) -> Result<Vec<Blueprint>, ParseError> {
+    lines(input)
+        .map(|(n, l)| {
+            let (_, recipes) = l
+                .split_once(':')
+                .filter(|(id, _)| id.starts_with("Blueprint "))
+                .ok_or_else(|| ParseError::line(n, l, "expected `Blueprint n:`"))?;
+
+            let mut parsed = vec![];
+            for recipe in recipes.split('.').map(str::trim).filter(|r| !r.is_empty()) {
+                let (robot, costs) = recipe
+                    .strip_prefix("Each ")
+                    .and_then(|r| r.split_once(" robot costs "))
+                    .ok_or_else(|| ParseError::token(n, l, recipe, "expected a robot recipe"))?;
+                let costs = costs
+                    .split(" and ")
+                    .map(|cost| {
+                        let (amount, resource) = cost
+                            .split_once(' ')
+                            .ok_or_else(|| ParseError::token(n, l, cost, "expected a cost"))?;
+                        Ok((number::<Mem>(n, l, amount)?, resource))
+                    })
+                    .collect::<Result<Vec<_>, ParseError>>()?;
+
+                if parsed.iter().any(|(r, _, _)| *r == robot) {
+                    return Err(ParseError::token(n, l, recipe, "robot recipe listed twice"));
+                }
+                if !costs.iter().map(|(_, r)| r).all_unique() {
+                    return Err(ParseError::token(n, l, recipe, "resource listed twice"));
+                }
+                parsed.push((robot, recipe, costs));
+            }
+
+            let names = parsed
+                .iter()
+                .map(|(robot, _, _)| robot.to_string())
+                .collect_vec();
+            let mut costs = vec![vec![0; names.len()]; names.len()];
+            for (i, (_, recipe, cost)) in parsed.iter().enumerate() {
+                for (amount, resource) in cost {
+                    let j = names.iter().position(|r| r == resource).ok_or_else(|| {
+                        ParseError::token(n, l, recipe, format!("no robot collects {resource}"))
+                    })?;
+                    costs[i][j] = *amount;
+                }
             }
+
+            let robot = |name| {
+                names
+                    .iter()
+                    .position(|r| r == name)
+                    .ok_or_else(|| ParseError::end(n, l, format!("missing the {name} robot")))
+            };
+            Ok(Blueprint {
+                ore: robot("ore")?,
+                geode: robot("geode")?,
+                names,
+                costs,
+            })
         })
         .collect()
 }
 
-type Cache = HashSet<State>;
-
-#[derive(Eq, PartialEq, Hash, Copy, Clone)]
-struct Counts {
-    ore: Mem,
-    clay: Mem,
-    obsidian: Mem,
-    geode: Mem,
-}
+// best result so far and the builds of the branch currently explored
+struct Search<'a> {
+    blueprint: &'a Blueprint,
+    minutes: Mem,
+    // no use collecting more per minute than can be spent
+    max_robots: Vec<Mem>,
 
-#[derive(Eq, PartialEq, Hash)]
-struct State {
     minute: Mem,
-    goods: Counts,
-    robots: Counts,
+    goods: Vec<Mem>,
+    robots: Vec<Mem>,
+
+    best: Mem,
+    builds: Vec<Option<usize>>,
+    current: Vec<Option<usize>>,
 }
 
-fn gauss(n: u32) -> u32 {
+fn gauss(n: u64) -> u64 {
     n * (n + 1) / 2
 }
 
-fn solve<const MINUTES: u8>(
-    blueprint: &Blueprint,
-    state: State,
-    max_robots: &Counts,
-    memo: &mut Cache,
-    best: &mut Mem,
-) {
-    if state.minute == MINUTES && state.goods.geode > *best {
-        *best = state.goods.geode
+impl Search<'_> {
+    // collect for `minutes` and pay `cost`
+    fn collect(&mut self, minutes: Mem, cost: &[Mem]) {
+        for (g, (r, c)) in self.goods.iter_mut().zip(self.robots.iter().zip(cost)) {
+            *g = r
+                .checked_mul(minutes)
+                .and_then(|collected| collected.checked_add(*g))
+                .expect("resource count overflowed")
+                - c;
+        }
     }
 
-    let remaining = MINUTES as u32 - state.minute as u32;
-    if state.goods.geode as u32 + state.robots.geode as u32 * remaining + gauss(remaining)
-        <= *best as u32
-    {
-        return;
+    // undoes `collect`
+    fn uncollect(&mut self, minutes: Mem, cost: &[Mem]) {
+        for (g, (r, c)) in self.goods.iter_mut().zip(self.robots.iter().zip(cost)) {
+            *g = *g + c - r * minutes;
+        }
     }
 
-    if memo.contains(&state) {
-        return;
+    // minutes of collecting until `cost` is affordable
+    fn wait(&self, cost: &[Mem]) -> Option<Mem> {
+        (0..cost.len()).try_fold(0, |wait, r| {
+            let (have, need, rate) = (self.goods[r], cost[r], self.robots[r]);
+            if have >= need {
+                Some(wait)
+            } else if rate == 0 {
+                None
+            } else {
+                Some(wait.max((need - have).div_ceil(rate)))
+            }
+        })
     }
 
-    let next_goods = Counts {
-        ore: state.goods.ore + state.robots.ore,
-        clay: state.goods.clay + state.robots.clay,
-        obsidian: state.goods.obsidian + state.robots.obsidian,
-        geode: state.goods.geode + state.robots.geode,
-    };
-
-    // greedily build geode robot
-    if state.goods.ore >= blueprint.geode.0
-        && state.goods.obsidian >= blueprint.geode.1
-        && state.robots.geode + 1 < max_robots.geode
-    {
-        solve::<MINUTES>(
-            blueprint,
-            State {
-                minute: state.minute + 1,
-                goods: Counts {
-                    ore: next_goods.ore - blueprint.geode.0,
-                    obsidian: next_goods.obsidian - blueprint.geode.1,
-                    ..next_goods
-                },
-                robots: Counts {
-                    geode: state.robots.geode + 1,
-                    ..state.robots
-                },
-            },
-            max_robots,
-            memo,
-            best,
-        );
-    } else {
-        // just collect resources
-        solve::<MINUTES>(
-            blueprint,
-            State {
-                minute: state.minute + 1,
-                goods: next_goods,
-                robots: state.robots,
-            },
-            max_robots,
-            memo,
-            best,
-        );
+    // branches on the next robot to build, waiting until it is affordable
+    fn solve(&mut self) {
+        let geode = self.blueprint.geode;
 
-        // try building the others
-        if state.goods.ore >= blueprint.ore && state.robots.ore + 1 < max_robots.ore {
-            solve::<MINUTES>(
-                blueprint,
-                State {
-                    minute: state.minute + 1,
-                    goods: Counts {
-                        ore: next_goods.ore - blueprint.ore,
-                        ..next_goods
-                    },
-                    robots: Counts {
-                        ore: state.robots.ore + 1,
-                        ..state.robots
-                    },
-                },
-                max_robots,
-                memo,
-                best,
-            );
+        // build nothing more
+        let remaining = self.minutes - self.minute;
+        let idle = self.robots[geode]
+            .checked_mul(remaining)
+            .and_then(|g| g.checked_add(self.goods[geode]))
+            .expect("resource count overflowed");
+        if idle > self.best {
+            self.best = idle;
+            self.builds = self.current.clone();
         }
 
-        if state.goods.ore >= blueprint.clay && state.robots.clay + 1 < max_robots.clay {
-            solve::<MINUTES>(
-                blueprint,
-                State {
-                    minute: state.minute + 1,
-                    goods: Counts {
-                        ore: next_goods.ore - blueprint.clay,
-                        ..next_goods
-                    },
-                    robots: Counts {
-                        clay: state.robots.clay + 1,
-                        ..state.robots
-                    },
-                },
-                max_robots,
-                memo,
-                best,
-            );
+        // a new geode robot every minute from now on
+        if idle as u64 + gauss(remaining.saturating_sub(1) as u64) <= self.best as u64 {
+            return;
         }
 
-        if state.goods.ore >= blueprint.obsidian.0
-            && state.goods.clay >= blueprint.obsidian.1
-            && state.robots.obsidian + 1 < max_robots.obsidian
-        {
-            solve::<MINUTES>(
-                blueprint,
-                State {
-                    minute: state.minute + 1,
-                    goods: Counts {
-                        ore: next_goods.ore - blueprint.obsidian.0,
-                        clay: next_goods.clay - blueprint.obsidian.1,
-                        ..next_goods
-                    },
-                    robots: Counts {
-                        obsidian: state.robots.obsidian + 1,
-                        ..state.robots
-                    },
-                },
-                max_robots,
-                memo,
-                best,
-            )
+        // geodes first, then the others starting with the most advanced
+        let order =
+            std::iter::once(geode).chain((0..self.robots.len()).rev().filter(|&r| r != geode));
+        for robot in order {
+            if self.robots[robot] >= self.max_robots[robot] {
+                continue;
+            }
+
+            let cost = &self.blueprint.costs[robot];
+            let Some(wait) = self.wait(cost) else {
+                continue;
+            };
+            // the robot has to be ready before the end to collect anything
+            if self.minute + wait + 1 >= self.minutes {
+                continue;
+            }
+
+            self.collect(wait + 1, cost);
+            self.robots[robot] += 1;
+            self.minute += wait + 1;
+            let len = self.current.len();
+            self.current
+                .extend(std::iter::repeat_n(None, wait as usize));
+            self.current.push(Some(robot));
+
+            self.solve();
+
+            self.current.truncate(len);
+            self.minute -= wait + 1;
+            self.robots[robot] -= 1;
+            self.uncollect(wait + 1, cost);
         }
     }
-
-    memo.insert(state);
 }
 
-fn max_geodes<const MINUTES: u8>(blueprint: &Blueprint) -> Mem {
-    let mut cache = Cache::new();
-    let state = State {
+/// Most geodes a blueprint can open in `minutes` and the builds getting
+/// there.
+pub fn max_geodes(blueprint: &Blueprint, minutes: Mem) -> Report {
+    let n = blueprint.names.len();
+    let mut robots = vec![0; n];
+    robots[blueprint.ore] = 1;
+
+    let mut search = Search {
+        blueprint,
+        minutes,
+        max_robots: (0..n)
+            .map(|r| match r {
+                _ if r == blueprint.geode => Mem::MAX,
+                _ => blueprint.costs.iter().map(|c| c[r]).max().unwrap_or(0),
+            })
+            .collect(),
         minute: 0,
-        goods: Counts {
-            ore: 0,
-            clay: 0,
-            obsidian: 0,
-            geode: 0,
-        },
-        robots: Counts {
-            ore: 1,
-            clay: 0,
-            obsidian: 0,
-            geode: 0,
-        },
-    };
-    let max_robots = Counts {
-        ore: *[
-            blueprint.ore,
-            blueprint.clay,
-            blueprint.obsidian.0,
-            blueprint.geode.0,
-        ]
-        .iter()
-        .max()
-        .unwrap()
-            + 1,
-        clay: blueprint.obsidian.1 + 1,
-        obsidian: blueprint.geode.1 + 1,
-        geode: Mem::MAX,
+        goods: vec![0; n],
+        robots,
+        best: 0,
+        builds: vec![],
+        current: vec![],
     };
+    search.solve();
+
+    let mut builds = search.builds;
+    builds.resize(minutes as usize, None);
+    Report::replay(blueprint, builds)
+}
+
+/// Build order with the goods and robots at the end of every minute.
+pub struct Report {
+    pub names: Vec<String>,
+    pub geodes: Mem,
+    pub builds: Vec<Option<usize>>,
+    pub ledger: Vec<(Vec<Mem>, Vec<Mem>)>,
+}
+
+impl Report {
+    // panics if a robot is built without the goods to pay for it
+    fn replay(blueprint: &Blueprint, builds: Vec<Option<usize>>) -> Self {
+        let n = blueprint.names.len();
+        let mut goods: Vec<Mem> = vec![0; n];
+        let mut robots = vec![0; n];
+        robots[blueprint.ore] = 1;
+
+        let mut ledger = vec![];
+        for build in builds.iter() {
+            for r in 0..n {
+                let cost = build.map_or(0, |b| blueprint.costs[b][r]);
+                goods[r] = goods[r]
+                    .checked_sub(cost)
+                    .expect("can't afford the robot")
+                    .checked_add(robots[r])
+                    .expect("resource count overflowed");
+            }
+            if let Some(robot) = build {
+                robots[*robot] += 1;
+            }
+            ledger.push((goods.clone(), robots.clone()));
+        }
+
+        Report {
+            names: blueprint.names.clone(),
+            geodes: goods[blueprint.geode],
+            builds,
+            ledger,
+        }
+    }
+}
 
-    let mut best = 0;
-    solve::<MINUTES>(blueprint, state, &max_robots, &mut cache, &mut best);
-    best
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let build = self.names.iter().map(String::len).max().unwrap_or(0).max(5);
+        let columns = self
+            .names
+            .iter()
+            .map(|name| format!("{name:>w$}", w = name.len().max(4)))
+            .join(" ");
+        writeln!(f, "min {:<build$} | {columns} | robots {columns}", "build")?;
+
+        for (minute, (robot, (goods, robots))) in self.builds.iter().zip(&self.ledger).enumerate() {
+            let robot = robot.map_or("-", |r| &self.names[r]);
+            let counts = |counts: &[Mem]| {
+                counts
+                    .iter()
+                    .zip(&self.names)
+                    .map(|(c, name)| format!("{c:>w$}", w = name.len().max(4)))
+                    .join(" ")
+            };
+            writeln!(
+                f,
+                "{:>3} {robot:<build$} | {} |        {}",
+                minute + 1,
+                counts(goods),
+                counts(robots)
+            )?;
+        }
+        write!(f, "{} geodes", self.geodes)
+    }
 }
 
 #[aoc(day19, part1)]
@@ -228,7 +303,7 @@ pub fn solve_part1(blueprints: &[Blueprint]) -> usize {
     blueprints
         .par_iter()
         .enumerate()
-        .map(|(i, b)| (i + 1) * max_geodes::<24>(b) as usize)
+        .map(|(i, b)| (i + 1) * max_geodes(b, 24).geodes as usize)
         .sum()
 }
 
@@ -237,7 +312,7 @@ pub fn solve_part2(blueprints: &[Blueprint]) -> usize {
     blueprints
         .par_iter()
         .take(3)
-        .map(|b| max_geodes::<32>(b) as usize)
+        .map(|b| max_geodes(b, 32).geodes as usize)
         .product()
 }
 
@@ -246,9 +321,71 @@ mod tests {
     const EXAMPLE: &str = "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay.Each geode robot costs 2 ore and 7 obsidian.
 Blueprint 2: Each ore robot costs 2 ore.Each clay robot costs 3 ore. Each obsidian robot costs 3 ore and 8 clay. Each geode robot costs 3 ore and 12 obsidian.";
 
+    #[test]
+    fn check_malformed() {
+        let err = super::parse(&EXAMPLE.replace("and 14 clay", "and 14 ore"))
+            .err()
+            .unwrap();
+        assert_eq!((err.line, err.column), (1, 71));
+        assert_eq!(err.text, "Each obsidian robot costs 3 ore and 14 ore");
+        assert_eq!(err.message, "resource listed twice");
+
+        let err = super::parse(&EXAMPLE.replace("and 14 clay", "and 14 mud"))
+            .err()
+            .unwrap();
+        assert_eq!(err.message, "no robot collects mud");
+
+        let err = super::parse("Blueprint 1: Each ore robot costs 4 ore.")
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 1, column 41: missing the geode robot"
+        );
+    }
+
     #[test]
     fn check_part1() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part1(&generated), 33);
     }
+
+    #[test]
+    fn check_report() {
+        let generated = super::parse(EXAMPLE).unwrap();
+        let report = super::max_geodes(&generated[0], 24);
+        assert_eq!(report.geodes, 9);
+        assert_eq!(report.builds.len(), 24);
+        let geode = generated[0].resource("geode").unwrap();
+        assert_eq!(report.ledger.last().unwrap().0[geode], 9);
+        assert!(report.to_string().ends_with("9 geodes"));
+    }
+
+    #[test]
+    fn check_part2() {
+        let generated = super::parse(EXAMPLE).unwrap();
+        assert_eq!(super::solve_part2(&generated), 56 * 62);
+    }
+
+    #[test]
+    fn check_long_horizon() {
+        let generated = super::parse(EXAMPLE).unwrap();
+        assert_eq!(super::max_geodes(&generated[0], 40).geodes, 158);
+    }
+
+    #[test]
+    fn check_variant() {
+        // an extra crystal robot between clay and obsidian
+        let variant = "Blueprint 1: Each ore robot costs 2 ore. \
+            Each clay robot costs 2 ore. \
+            Each crystal robot costs 2 ore and 4 clay. \
+            Each obsidian robot costs 2 ore and 4 crystal. \
+            Each geode robot costs 2 ore and 4 obsidian.";
+        let generated = super::parse(variant).unwrap();
+        assert_eq!(generated[0].resource("crystal"), Some(2));
+
+        let report = super::max_geodes(&generated[0], 24);
+        assert!(report.geodes > 0);
+        assert!(report.to_string().contains("crystal"));
+    }
 }
@@ -1,5 +1,7 @@
 use core::panic;
 
+use crate::error::{lines, ParseError};
+
 pub enum Action {
     Rock,
     Paper,
@@ -70,18 +72,22 @@ impl Outcome {
 pub type Round = (Action, char);
 
 #[aoc_generator(day2)]
-pub fn parse_strategy(input: &str) -> Vec<Round> {
-    input
-        .lines()
-        .map(|l| {
+pub fn parse_strategy(input: &str) -> Result<Vec<Round>, ParseError> {
+    lines(input)
+        .map(|(n, l)| {
             let mut parsed = l.trim().split(' ');
-            let opponent = match parsed.next().unwrap() {
+            let opponent = match parsed.next().unwrap_or_default() {
                 "A" => Action::Rock,
                 "B" => Action::Paper,
                 "C" => Action::Scissor,
-                _ => panic!("unexpected character!"),
+                s => return Err(ParseError::token(n, l, s, "expected A, B or C")),
+            };
+            let me = match parsed.next() {
+                Some(s @ ("X" | "Y" | "Z")) => s.chars().next().unwrap(),
+                Some(s) => return Err(ParseError::token(n, l, s, "expected X, Y or Z")),
+                None => return Err(ParseError::end(n, l, "expected X, Y or Z")),
             };
-            (opponent, parsed.next().unwrap().chars().next().unwrap())
+            Ok((opponent, me))
         })
         .collect()
 }
@@ -1,11 +1,10 @@
 use itertools::Itertools;
 
+use crate::error::{lines, number, ParseError};
+
 #[aoc_generator(day20)]
-pub fn parse(input: &str) -> Vec<i64> {
-    input
-        .lines()
-        .filter_map(|l| l.parse::<i64>().ok())
-        .collect()
+pub fn parse(input: &str) -> Result<Vec<i64>, ParseError> {
+    lines(input).map(|(n, l)| number(n, l, l)).collect()
 }
 
 fn mix_pos(i: usize, original: &[i64], indices: &mut Vec<usize>, key: i64) {
@@ -89,13 +88,13 @@ mod tests {
 
     #[test]
     fn check_part1() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part1(&generated), 3);
     }
 
     #[test]
     fn check_part2() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part2(&generated), 1623178306);
     }
 }
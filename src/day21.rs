@@ -1,9 +1,15 @@
 use std::{
     collections::{HashMap, VecDeque},
+    error::Error,
     fmt::Display,
     rc::Rc,
 };
 
+use itertools::Itertools;
+use num::{BigRational, One, ToPrimitive, Zero};
+
+use crate::error::{lines, number, ParseError};
+
 pub enum Expression {
     Num(Option<u64>),
     Add(Rc<Expression>, Rc<Expression>),
@@ -13,76 +19,115 @@ pub enum Expression {
 }
 
 impl Expression {
-    fn get_terms(&self) -> Option<(Rc<Expression>, Rc<Expression>)> {
-        match self {
-            Self::Num(_) => None,
-            Self::Add(a, b) => Some((a.clone(), b.clone())),
-            Self::Sub(a, b) => Some((a.clone(), b.clone())),
-            Self::Mul(a, b) => Some((a.clone(), b.clone())),
-            Self::Div(a, b) => Some((a.clone(), b.clone())),
-        }
-    }
+    /// Simplifies to `a * humn + b`, failing if `humn` isn't linear.
+    pub fn linear(&self) -> Result<Linear, SolveError> {
+        let (lhs, rhs) = match self {
+            Self::Num(Some(n)) => {
+                return Ok(Linear::constant(BigRational::from_integer((*n).into())))
+            }
+            Self::Num(None) => {
+                return Ok(Linear {
+                    a: BigRational::one(),
+                    b: BigRational::zero(),
+                })
+            }
+            Self::Add(a, b) | Self::Sub(a, b) | Self::Mul(a, b) | Self::Div(a, b) => {
+                (a.linear()?, b.linear()?)
+            }
+        };
 
-    fn eval(&self) -> Option<u64> {
-        match self {
-            Self::Num(n) => *n,
-            _ => {
-                let (a, b) = self.get_terms().unwrap();
-                let a = a.eval();
-                let b = b.eval();
-                match (a, b) {
-                    (Some(a), Some(b)) => match self {
-                        Self::Add(_, _) => Some(a + b),
-                        Self::Sub(_, _) => Some(a - b),
-                        Self::Mul(_, _) => Some(a * b),
-                        Self::Div(_, _) => Some(a / b),
-                        _ => unreachable!(),
-                    },
-                    _ => None,
+        Ok(match self {
+            Self::Add(_, _) => Linear {
+                a: lhs.a + rhs.a,
+                b: lhs.b + rhs.b,
+            },
+            Self::Sub(_, _) => Linear {
+                a: lhs.a - rhs.a,
+                b: lhs.b - rhs.b,
+            },
+            Self::Mul(_, _) => match (lhs.a.is_zero(), rhs.a.is_zero()) {
+                (_, true) => lhs.scale(&rhs.b),
+                (true, false) => rhs.scale(&lhs.b),
+                (false, false) => return Err(SolveError::NonLinear(self.to_string())),
+            },
+            Self::Div(_, _) => {
+                if !rhs.a.is_zero() {
+                    return Err(SolveError::NonLinear(self.to_string()));
+                }
+                if rhs.b.is_zero() {
+                    return Err(SolveError::DivisionByZero(self.to_string()));
                 }
+                lhs.scale(&rhs.b.recip())
             }
+            Self::Num(_) => unreachable!(),
+        })
+    }
+
+    /// The `humn` making both sides equal, which may appear on either side.
+    pub fn solve(lhs: &Expression, rhs: &Expression) -> Result<BigRational, SolveError> {
+        let (lhs, rhs) = (lhs.linear()?, rhs.linear()?);
+        let a = lhs.a - rhs.a;
+        let b = rhs.b - lhs.b;
+
+        match (a.is_zero(), b.is_zero()) {
+            (true, true) => Err(SolveError::Infinite),
+            (true, false) => Err(SolveError::NoSolution),
+            _ => Ok(b / a),
         }
     }
+}
 
-    fn solve_for(mut lhs: Rc<Expression>, mut rhs: Rc<Expression>) -> Option<u64> {
-        if let Some((a, b)) = lhs.get_terms() {
-            let ax = a.eval();
-            let bx = b.eval();
+/// `a * humn + b` with exact rational coefficients.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Linear {
+    pub a: BigRational,
+    pub b: BigRational,
+}
 
-            if let Some(x) = ax {
-                let x = Rc::new(Expression::Num(Some(x)));
-                rhs = match &*lhs {
-                    Self::Add(_, _) => Rc::new(Expression::Sub(rhs, x)),
-                    Self::Sub(_, _) => Rc::new(Expression::Sub(x, rhs)),
-                    Self::Mul(_, _) => Rc::new(Expression::Div(rhs, x)),
-                    Self::Div(_, _) => Rc::new(Expression::Mul(x, rhs)),
-                    _ => unreachable!(),
-                };
-                lhs = b;
-            } else if let Some(x) = bx {
-                let x = Rc::new(Expression::Num(Some(x)));
-                rhs = match &*lhs {
-                    Self::Add(_, _) => Rc::new(Expression::Sub(rhs, x)),
-                    Self::Sub(_, _) => Rc::new(Expression::Add(rhs, x)),
-                    Self::Mul(_, _) => Rc::new(Expression::Div(rhs, x)),
-                    Self::Div(_, _) => Rc::new(Expression::Mul(rhs, x)),
-                    _ => unreachable!(),
-                };
-                lhs = a;
-            }
-            Expression::solve_for(lhs, rhs)
-        } else {
-            rhs.eval()
+impl Linear {
+    fn constant(b: BigRational) -> Self {
+        Linear {
+            a: BigRational::zero(),
+            b,
+        }
+    }
+
+    fn scale(self, k: &BigRational) -> Self {
+        Linear {
+            a: self.a * k,
+            b: self.b * k,
         }
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    // the offending sub-expression
+    NonLinear(String),
+    DivisionByZero(String),
+    NoSolution,
+    Infinite,
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NonLinear(expr) => write!(f, "humn isn't linear in {expr}"),
+            Self::DivisionByZero(expr) => write!(f, "division by zero in {expr}"),
+            Self::NoSolution => write!(f, "no value of humn makes both sides equal"),
+            Self::Infinite => write!(f, "every value of humn makes both sides equal"),
+        }
+    }
+}
+
+impl Error for SolveError {}
+
 impl Display for Expression {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Num(n) => match n {
                 Some(n) => write!(f, "{n}"),
-                None => write!(f, "x"),
+                None => write!(f, "humn"),
             },
             Self::Add(a, b) => write!(f, "({a}+{b})"),
             Self::Sub(a, b) => write!(f, "({a}-{b})"),
@@ -111,33 +156,59 @@ fn add_edge(from: &String, to: &String, edges: &mut Edges) {
 }
 
 #[aoc_generator(day21)]
-pub fn parse(input: &str) -> Parsed {
+pub fn parse(input: &str) -> Result<Parsed, ParseError> {
     let mut edges = HashMap::<String, Vec<String>>::new();
-    let monkeys: HashMap<String, Monkey> = input
-        .replace(':', "")
-        .lines()
-        .map(|l| {
-            let mut split = l.split(' ');
-            let label = split.next().unwrap().to_owned();
-            let a = split.next().unwrap().to_owned();
-
-            let monkey = if let Ok(n) = a.parse::<u64>() {
-                Monkey::Val(Some(n))
-            } else {
-                let op = split.next().unwrap().chars().next().unwrap();
-                let b = split.next().unwrap().to_owned();
-
-                add_edge(&a, &label, &mut edges);
-                add_edge(&b, &label, &mut edges);
-
-                Monkey::Cal(a, b, op)
+    let mut references = vec![];
+    let monkeys: HashMap<String, Monkey> = lines(input)
+        .map(|(n, l)| {
+            let (label, job) = l
+                .split_once(": ")
+                .ok_or_else(|| ParseError::line(n, l, "expected `name: job`"))?;
+            let label = label.to_owned();
+
+            let monkey = match job.split(' ').collect_vec()[..] {
+                [a] => Monkey::Val(Some(number(n, l, a)?)),
+                [a, op @ ("+" | "-" | "*" | "/"), b] => {
+                    references.push((n, l, a));
+                    references.push((n, l, b));
+
+                    let (a, b) = (a.to_owned(), b.to_owned());
+                    add_edge(&a, &label, &mut edges);
+                    add_edge(&b, &label, &mut edges);
+
+                    Monkey::Cal(a, b, op.chars().next().unwrap())
+                }
+                _ => {
+                    return Err(ParseError::token(
+                        n,
+                        l,
+                        job,
+                        "expected a number or `a op b`",
+                    ))
+                }
             };
 
-            (label, monkey)
+            Ok((label, monkey))
         })
-        .collect();
+        .collect::<Result<_, _>>()?;
+
+    for (n, l, name) in references {
+        if !monkeys.contains_key(name) {
+            return Err(ParseError::token(n, l, name, "unknown monkey"));
+        }
+    }
+    for name in ["root", "humn"] {
+        if !monkeys.contains_key(name) {
+            let eof = input.lines().count() + 1;
+            return Err(ParseError::end(
+                eof,
+                "",
+                format!("couldn't find monkey {name}"),
+            ));
+        }
+    }
 
-    (monkeys, edges)
+    Ok((monkeys, edges))
 }
 
 fn expressions<F>((monkeys, edges): &Parsed, init: F) -> HashMap<String, Rc<Expression>>
@@ -185,18 +256,53 @@ where
     visited
 }
 
-#[aoc(day21, part1)]
-pub fn solve_part1(parsed: &Parsed) -> u64 {
-    let expressions = expressions(parsed, |label, monkey| match monkey {
-        Monkey::Val(_) => Some((label.to_owned(), monkey.clone())),
-        _ => None,
+// the puzzle answers are whole numbers
+fn integer(n: BigRational) -> i64 {
+    n.is_integer()
+        .then(|| n.to_integer().to_i64())
+        .flatten()
+        .unwrap_or_else(|| panic!("{n} isn't a whole number"))
+}
+
+/// The number `humn` has to yell for `root` to see two equal numbers.
+pub fn solve_humn(parsed: &Parsed) -> Result<BigRational, SolveError> {
+    let expressions = expressions(parsed, |label, monkey| {
+        if label == "humn" {
+            Some((label.to_owned(), Monkey::Val(None)))
+        } else {
+            match monkey {
+                Monkey::Val(_) => Some((label.to_owned(), monkey.clone())),
+                _ => None,
+            }
+        }
     });
 
-    expressions.get("root").unwrap().eval().unwrap()
+    match parsed.0.get("root").unwrap() {
+        Monkey::Val(_) => Err(SolveError::NoSolution),
+        Monkey::Cal(a, b, _) => Expression::solve(&expressions[a], &expressions[b]),
+    }
 }
 
-#[aoc(day21, part2)]
-pub fn solve_part2(parsed: &Parsed) -> u64 {
+// `expr` with every subtree without `humn` folded into its value
+fn infix(expr: &Expression) -> String {
+    if let Ok(Linear { a, b }) = expr.linear() {
+        if a.is_zero() {
+            return b.to_string();
+        }
+    }
+
+    let (a, b, op) = match expr {
+        Expression::Num(_) => return "humn".to_owned(),
+        Expression::Add(a, b) => (a, b, '+'),
+        Expression::Sub(a, b) => (a, b, '-'),
+        Expression::Mul(a, b) => (a, b, '*'),
+        Expression::Div(a, b) => (a, b, '/'),
+    };
+    format!("({} {op} {})", infix(a), infix(b))
+}
+
+/// The equation `root` checks, simplified as far as it goes without `humn`.
+pub fn root_equation(parsed: &Parsed) -> String {
     let expressions = expressions(parsed, |label, monkey| {
         if label == "humn" {
             Some((label.to_owned(), Monkey::Val(None)))
@@ -208,23 +314,83 @@ pub fn solve_part2(parsed: &Parsed) -> u64 {
         }
     });
 
-    let monkeys = &parsed.0;
-    let root = monkeys.get("root").unwrap();
-    match root {
-        Monkey::Val(_) => unreachable!(),
+    match parsed.0.get("root").unwrap() {
+        Monkey::Val(_) => infix(&expressions["root"]),
         Monkey::Cal(a, b, _) => {
-            let lhs = expressions.get(a).unwrap().clone();
+            let trim = |s: String| match s.strip_prefix('(') {
+                Some(s) => s.strip_suffix(')').unwrap().to_owned(),
+                None => s,
+            };
+            format!(
+                "{} = {}",
+                trim(infix(&expressions[a])),
+                trim(infix(&expressions[b]))
+            )
+        }
+    }
+}
 
-            let b = expressions.get(b).unwrap().eval().unwrap();
-            let rhs = Rc::new(Expression::Num(Some(b)));
+/// Graphviz DOT of which monkey listens to which, every monkey is labelled
+/// with its job and the number it yells.
+pub fn dot(parsed: &Parsed) -> String {
+    let (monkeys, edges) = parsed;
+    let expressions = expressions(parsed, |label, monkey| match monkey {
+        Monkey::Val(_) => Some((label.to_owned(), monkey.clone())),
+        _ => None,
+    });
 
-            Expression::solve_for(lhs, rhs).unwrap()
-        }
+    let mut dot = String::from("digraph monkeys {\n");
+    for label in monkeys.keys().sorted() {
+        let job = match &monkeys[label] {
+            Monkey::Val(Some(n)) => n.to_string(),
+            Monkey::Val(None) => "?".to_owned(),
+            Monkey::Cal(a, b, op) => format!("{a} {op} {b}"),
+        };
+        let value = match expressions.get(label).map(|e| e.linear()) {
+            Some(Ok(Linear { b, .. })) => b.to_string(),
+            _ => "?".to_owned(),
+        };
+        let style = match label.as_str() {
+            "root" | "humn" => ", style=bold",
+            _ => "",
+        };
+        dot += &format!("    {label} [label=\"{label}: {job}\\n= {value}\"{style}];\n");
+    }
+    for (from, to) in edges
+        .iter()
+        .flat_map(|(from, to)| to.iter().map(move |to| (from, to)))
+        .sorted()
+    {
+        dot += &format!("    {from} -> {to};\n");
     }
+    dot += "}\n";
+    dot
+}
+
+#[aoc(day21, part1)]
+pub fn solve_part1(parsed: &Parsed) -> i64 {
+    let expressions = expressions(parsed, |label, monkey| match monkey {
+        Monkey::Val(_) => Some((label.to_owned(), monkey.clone())),
+        _ => None,
+    });
+
+    let root = expressions["root"]
+        .linear()
+        .unwrap_or_else(|e| panic!("{e}"));
+    integer(root.b)
+}
+
+#[aoc(day21, part2)]
+pub fn solve_part2(parsed: &Parsed) -> i64 {
+    integer(solve_humn(parsed).unwrap_or_else(|e| panic!("{e}")))
 }
 
 #[cfg(test)]
 mod tests {
+    use num::BigRational;
+
+    use super::SolveError;
+
     const EXAMPLE: &str = "root: pppw + sjmn
 dbpl: 5
 cczh: sllz + lgvd
@@ -243,13 +409,83 @@ hmdt: 32";
 
     #[test]
     fn check_part1() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part1(&generated), 152);
     }
 
     #[test]
     fn check_part2() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part2(&generated), 301);
     }
+
+    fn humn(root: &str, monkeys: &str) -> Result<BigRational, SolveError> {
+        let input = format!("root: {root}\nhumn: 5\nfive: 5\nzero: 0\n{monkeys}");
+        super::solve_humn(&super::parse(&input).unwrap())
+    }
+
+    #[test]
+    fn check_solver() {
+        // humn on both sides, 2 * humn = humn + 3
+        assert_eq!(
+            humn(
+                "dbl + inc",
+                "dbl: humn * two\ntwo: 2\ninc: humn + three\nthree: 3"
+            ),
+            Ok(BigRational::from_integer(3.into()))
+        );
+        // goes negative on the way, (5 - humn) / 2 = 5
+        assert_eq!(
+            humn("half + five", "half: diff / two\ndiff: five - humn\ntwo: 2"),
+            Ok(BigRational::from_integer((-5).into()))
+        );
+        // not a whole number, humn / 2 = 5 / 4
+        assert_eq!(
+            humn(
+                "half + quarter",
+                "half: humn / two\ntwo: 2\nquarter: five / four\nfour: 4"
+            ),
+            Ok(BigRational::new(5.into(), 2.into()))
+        );
+
+        assert_eq!(
+            humn("sq + five", "sq: humn * humn")
+                .unwrap_err()
+                .to_string(),
+            "humn isn't linear in (humn*humn)"
+        );
+        assert_eq!(
+            humn("inv + five", "inv: five / humn"),
+            Err(SolveError::NonLinear("(5/humn)".to_owned()))
+        );
+        assert_eq!(
+            humn("inv + five", "inv: humn / zero"),
+            Err(SolveError::DivisionByZero("(humn/0)".to_owned()))
+        );
+        assert_eq!(
+            humn("humn + inc", "inc: humn + five"),
+            Err(SolveError::NoSolution)
+        );
+        assert_eq!(
+            humn("humn + same", "same: humn + zero"),
+            Err(SolveError::Infinite)
+        );
+    }
+
+    #[test]
+    fn check_export() {
+        let generated = super::parse(EXAMPLE).unwrap();
+        assert_eq!(
+            super::root_equation(&generated),
+            "(4 + (2 * (humn - 3))) / 4 = 150"
+        );
+
+        let dot = super::dot(&generated);
+        assert!(dot.starts_with("digraph monkeys {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains("    cczh [label=\"cczh: sllz + lgvd\\n= 8\"];\n"));
+        assert!(dot.contains("    root [label=\"root: pppw + sjmn\\n= 152\", style=bold];\n"));
+        assert!(dot.contains("    humn -> ptdq;\n"));
+        assert_eq!(dot.matches(" -> ").count(), 14);
+    }
 }
@@ -1,6 +1,10 @@
 use std::{fmt::Display, iter::repeat};
 
-use crate::vec::Vec2;
+use crate::{
+    error::{lines, number, ParseError},
+    grid::Grid,
+    vec::{Vec2, Vec3},
+};
 use itertools::Itertools;
 use num::integer::gcd;
 
@@ -52,10 +56,47 @@ impl Facing {
     fn dir(&self) -> Vec2 {
         // (0, 0) top left, y increases downwards
         match self {
-            Self::Right => Vec2 { x: 1, y: 0 },
-            Self::Down => Vec2 { x: 0, y: 1 },
-            Self::Left => Vec2 { x: -1, y: 0 },
-            Self::Up => Vec2 { x: 0, y: -1 },
+            Self::Right => Vec2::RIGHT,
+            Self::Down => Vec2::DOWN,
+            Self::Left => Vec2::LEFT,
+            Self::Up => Vec2::UP,
+        }
+    }
+
+    fn from_dir(dir: Vec2) -> Self {
+        match (dir.x, dir.y) {
+            (1, 0) => Self::Right,
+            (0, 1) => Self::Down,
+            (-1, 0) => Self::Left,
+            (0, -1) => Self::Up,
+            _ => unreachable!("not a unit direction: {dir:?}"),
+        }
+    }
+}
+
+// orientation of a square of the net once folded onto the cube
+#[derive(Debug, Clone)]
+struct Face {
+    origin: Vec2,
+    normal: Vec3,
+    x: Vec3,
+    y: Vec3,
+}
+
+impl Face {
+    fn axis(&self, dir: Vec2) -> Vec3 {
+        self.x * dir.x + self.y * dir.y
+    }
+
+    // roll the cube over the edge in direction `dir`
+    fn roll(&self, dir: Vec2, k: i32) -> Face {
+        let edge = self.axis(dir);
+        let down = -self.normal;
+        Face {
+            origin: self.origin + dir * k,
+            normal: edge,
+            x: if dir.x != 0 { down * dir.x } else { self.x },
+            y: if dir.y != 0 { down * dir.y } else { self.y },
         }
     }
 }
@@ -69,37 +110,27 @@ pub enum Instruction {
 
 #[derive(Clone)]
 pub struct Board {
-    width: usize,
-    height: usize,
     square_size: usize,
+    faces: Vec<Option<Face>>,
 
-    board: Vec<Vec<char>>,
+    board: Grid<char>,
     instructions: Vec<Instruction>,
 }
 
 impl Board {
-    // TODO: if let else?
     fn get(&self, position: &Vec2) -> Option<char> {
-        if let Some(row) = self.board.get(position.y as usize) {
-            if let Some(c) = row.get(position.x as usize) {
-                return Some(*c);
-            }
-        }
-        None
+        self.board.get(*position).copied()
     }
 
     fn get_mut(&mut self, position: &Vec2) -> Option<&mut char> {
-        if let Some(row) = self.board.get_mut(position.y as usize) {
-            if let Some(c) = row.get_mut(position.x as usize) {
-                return Some(c);
-            }
-        }
-        None
+        self.board.get_mut(*position)
     }
 
     fn start(&self) -> (Vec2, Facing) {
         let position = Vec2 {
-            x: self.board[0]
+            x: self
+                .board
+                .row(0)
                 .iter()
                 .enumerate()
                 .find(|(_, &c)| c == '.')
@@ -113,30 +144,65 @@ impl Board {
 
     fn wrap2d(&self, position: &Vec2, facing: &Facing) -> (Vec2, Facing) {
         let mut next = *position + facing.dir();
-        next.x = next.x.rem_euclid(self.width as i32);
-        next.y = next.y.rem_euclid(self.height as i32);
+        next.x = next.x.rem_euclid(self.board.width() as i32);
+        next.y = next.y.rem_euclid(self.board.height() as i32);
         while let Some(c) = self.get(&next) {
             if c != ' ' {
                 break;
             }
             next += facing.dir();
-            next.x = next.x.rem_euclid(self.width as i32);
-            next.y = next.y.rem_euclid(self.height as i32);
+            next.x = next.x.rem_euclid(self.board.width() as i32);
+            next.y = next.y.rem_euclid(self.board.height() as i32);
         }
         (next, *facing)
     }
 
     fn square(&self, position: &Vec2) -> i32 {
-        let a = position.x.rem_euclid(self.width as i32) / self.square_size as i32;
-        let b = position.y.rem_euclid(self.height as i32) / self.square_size as i32;
+        let a = position.x.rem_euclid(self.board.width() as i32) / self.square_size as i32;
+        let b = position.y.rem_euclid(self.board.height() as i32) / self.square_size as i32;
 
-        b * (self.width / self.square_size) as i32 + a
+        b * (self.board.width() / self.square_size) as i32 + a
+    }
+
+    fn fold(&self) -> Vec<Option<Face>> {
+        let k = self.square_size as i32;
+        let mut faces = vec![
+            None;
+            (self.board.width() / self.square_size)
+                * (self.board.height() / self.square_size)
+        ];
+
+        let (start, _) = self.start();
+        let origin = Vec2 {
+            x: start.x - start.x % k,
+            y: 0,
+        };
+        let mut queue = vec![Face {
+            origin,
+            normal: Vec3::Z,
+            x: Vec3::X,
+            y: Vec3::Y,
+        }];
+        while let Some(face) = queue.pop() {
+            let square = self.square(&face.origin) as usize;
+            if faces[square].is_some() {
+                continue;
+            }
+            for facing in [Facing::Right, Facing::Down, Facing::Left, Facing::Up] {
+                let next = face.roll(facing.dir(), k);
+                if !matches!(self.get(&next.origin), None | Some(' ')) {
+                    queue.push(next);
+                }
+            }
+            faces[square] = Some(face);
+        }
+        faces
     }
 
     fn wrap3d(&self, position: &Vec2, facing: &Facing) -> (Vec2, Facing) {
         let mut next = *position + facing.dir();
-        next.x = next.x.rem_euclid(self.width as i32);
-        next.y = next.y.rem_euclid(self.height as i32);
+        next.x = next.x.rem_euclid(self.board.width() as i32);
+        next.y = next.y.rem_euclid(self.board.height() as i32);
 
         let from = self.square(position);
         let to = self.square(&next);
@@ -151,97 +217,33 @@ impl Board {
         }
 
         let k = self.square_size as i32;
-        let x = position.x.rem_euclid(k);
-        let y = position.y.rem_euclid(k);
-
-        if self.square_size == 4 {
-            match (from, to) {
-                (6, 7) => (
-                    Vec2 {
-                        x: 3 * k + (k - 1) - y,
-                        y: 2 * k,
-                    },
-                    Facing::Down,
-                ),
-                (10, 2) => (
-                    Vec2 {
-                        x: (k - 1) - x,
-                        y: 2 * k - 1,
-                    },
-                    Facing::Up,
-                ),
-                (5, 1) => (Vec2 { x: 2 * k, y: x }, Facing::Right),
-                _ => unimplemented!("implement transition from {from} to {to}!"),
-            }
-        } else {
-            match (from, to) {
-                (1, 10) => (Vec2 { x: 0, y: 3 * k + x }, Facing::Right),
-                (9, 11) => (Vec2 { x: k + y, y: 0 }, Facing::Down),
-                (1, 0) => (
-                    Vec2 {
-                        x: 0,
-                        y: 2 * k + (k - 1) - y,
-                    },
-                    Facing::Right,
-                ),
-                (7, 10) => (
-                    Vec2 {
-                        x: k - 1,
-                        y: 3 * k + x,
-                    },
-                    Facing::Left,
-                ),
-                (6, 8) => (
-                    Vec2 {
-                        x: k,
-                        y: (k - 1) - y,
-                    },
-                    Facing::Right,
-                ),
-                (6, 3) => (Vec2 { x: k, y: k + x }, Facing::Right),
-                (4, 3) => (Vec2 { x: y, y: 2 * k }, Facing::Down),
-                (4, 5) => (
-                    Vec2 {
-                        x: 2 * k + y,
-                        y: k - 1,
-                    },
-                    Facing::Up,
-                ),
-                (2, 5) => (
-                    Vec2 {
-                        x: 2 * k - 1,
-                        y: k + x,
-                    },
-                    Facing::Left,
-                ),
-                (9, 0) => (Vec2 { x: 2 * k + x, y: 0 }, Facing::Down),
-                (2, 11) => (Vec2 { x, y: 4 * k - 1 }, Facing::Up),
-                (2, 0) => (
-                    Vec2 {
-                        x: 2 * k - 1,
-                        y: 2 * k + (k - 1) - y,
-                    },
-                    Facing::Left,
-                ),
-                (7, 8) => (
-                    Vec2 {
-                        x: 3 * k - 1,
-                        y: (k - 1) - y,
-                    },
-                    Facing::Left,
-                ),
-                (9, 10) => (
-                    Vec2 {
-                        x: k + y,
-                        y: 3 * k - 1,
-                    },
-                    Facing::Up,
-                ),
-                _ => {
-                    unimplemented!("implement transition from {from} to {to}, {facing}!");
-                }
-            }
-        }
+        let a = self.faces[from as usize].as_ref().unwrap();
+
+        // cell centers on a cube of side 2k centered at the origin,
+        // stepping over the edge moves half a cell along the edge normal
+        // and half a cell back along the face normal
+        let local = *position - a.origin;
+        let p = a.normal * k + a.x * (2 * local.x + 1 - k) + a.y * (2 * local.y + 1 - k);
+        let edge = a.axis(facing.dir());
+        let p = p + edge - a.normal;
+
+        let b = self
+            .faces
+            .iter()
+            .flatten()
+            .find(|f| f.normal == edge)
+            .unwrap();
+        let next = b.origin
+            + Vec2 {
+                x: (p.dot(b.x) + k - 1) / 2,
+                y: (p.dot(b.y) + k - 1) / 2,
+            };
+        let heading = -a.normal;
+        let facing = Facing::from_dir(Vec2 {
+            x: heading.dot(b.x),
+            y: heading.dot(b.y),
+        });
+        (next, facing)
     }
 
     fn traverse(&mut self, dim: u32) -> (Vec2, Facing) {
@@ -289,55 +291,96 @@ impl Board {
 
 impl Display for Board {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for row in self.board.iter() {
-            writeln!(f, "{}", row.iter().collect::<String>())?
-        }
-        Ok(())
+        write!(f, "{}", self.board)
     }
 }
 
 #[aoc_generator(day22)]
-pub fn parse(input: &str) -> Board {
-    let mut lines = input.lines();
+pub fn parse(input: &str) -> Result<Board, ParseError> {
+    let mut lines = lines(input);
+
+    let board_lines = lines
+        .take_while_ref(|(_, l)| !l.is_empty())
+        .map(
+            |(n, l)| match l.matches(|c| !matches!(c, ' ' | '.' | '#')).next() {
+                Some(c) => Err(ParseError::token(n, l, c, "expected ' ', '.' or '#'")),
+                None => Ok(l),
+            },
+        )
+        .collect::<Result<Vec<_>, _>>()?;
+    if !board_lines.first().is_some_and(|l| l.contains('.')) {
+        return Err(ParseError::end(
+            1,
+            "",
+            "expected an open tile in the first row",
+        ));
+    }
 
-    let board_lines = lines.take_while_ref(|l| !l.is_empty()).collect_vec();
     let n = board_lines.len();
     let m = board_lines.iter().map(|l| l.len()).max().unwrap();
     let k = gcd(n, m);
 
-    let board = board_lines
-        .iter()
-        .map(|l| l.chars().chain(repeat(' ')).take(m).collect_vec())
-        .collect_vec();
+    let board = Grid::from_rows(
+        board_lines
+            .iter()
+            .map(|l| l.chars().chain(repeat(' ')).take(m).collect_vec())
+            .collect_vec(),
+    );
 
-    let instructions = lines
+    let (i, l) = lines
         .nth(1)
-        .unwrap()
-        .split_inclusive(char::is_alphabetic)
-        .flat_map(|s| {
-            let mut num = s;
-            let mut facing = "";
-            if s.ends_with(char::is_alphabetic) {
-                (num, facing) = s.split_at(s.len() - 1);
+        .ok_or_else(|| ParseError::end(n + 2, "", "expected the path"))?;
+    let mut instructions = vec![];
+    let mut start = 0;
+    for (j, c) in l.char_indices() {
+        let turn = match c {
+            '0'..='9' => continue,
+            'R' => Instruction::Cw,
+            'L' => Instruction::Ccw,
+            _ => {
+                let c = &l[j..j + c.len_utf8()];
+                return Err(ParseError::token(i, l, c, "expected a number, R or L"));
             }
-            let facing = match facing {
-                "R" => Some(Instruction::Cw),
-                "L" => Some(Instruction::Ccw),
-                _ => None,
-            };
-
-            [Some(Instruction::Go(num.parse().unwrap())), facing]
-        })
-        .flatten()
-        .collect_vec();
+        };
+        instructions.push(Instruction::Go(number(i, l, &l[start..j])?));
+        instructions.push(turn);
+        start = j + 1;
+    }
+    instructions.push(Instruction::Go(number(i, l, &l[start..])?));
 
-    Board {
-        height: n,
-        width: m,
+    let mut board = Board {
         square_size: k,
+        faces: vec![],
         board,
         instructions,
+    };
+    board.faces = board.fold();
+
+    // every square of the net is a whole face folded onto its own side
+    for (y, x) in (0..n / k).cartesian_product(0..m / k) {
+        let open = (y * k..(y + 1) * k)
+            .cartesian_product(x * k..(x + 1) * k)
+            .filter(|&(y, x)| board.board[Vec2::new(x as i32, y as i32)] != ' ')
+            .count();
+        if open > 0 && (open < k * k || board.faces[y * (m / k) + x].is_none()) {
+            let (l, message) = (board_lines[y * k], "expected a face of the cube");
+            return Err(match l.get(x * k..((x + 1) * k).min(l.len())) {
+                Some(square) if !square.is_empty() => {
+                    ParseError::token(y * k + 1, l, square, message)
+                }
+                _ => ParseError::end(y * k + 1, l, message),
+            });
+        }
+    }
+    let normals = board.faces.iter().flatten().map(|f| f.normal).collect_vec();
+    if normals.len() != 6 || !normals.iter().all_unique() {
+        return Err(ParseError::end(
+            n + 1,
+            "",
+            "the map doesn't fold into a cube",
+        ));
     }
+    Ok(board)
 }
 
 #[aoc(day22, part1)]
@@ -396,19 +439,90 @@ mod tests {
 
     #[test]
     fn check_part1() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part1(&generated), 6032);
     }
 
+    #[test]
+    fn check_malformed() {
+        let err = super::parse(&EXAMPLE.replace("........#...", "... ....#..."))
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 5, column 1: expected a face of the cube, found `...#`"
+        );
+
+        let err = super::parse(&expand("#..\n.##", 2, "1")).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 3, column 3: expected a face of the cube, found `..`"
+        );
+
+        let err = super::parse(&expand("##\n##", 2, "1")).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 5, column 1: the map doesn't fold into a cube"
+        );
+    }
+
     #[test]
     fn check_fold_part2() {
-        let generated = super::parse(PART2_SHAPE);
+        let generated = super::parse(PART2_SHAPE).unwrap();
         super::solve_part2(&generated);
     }
 
     #[test]
     fn check_part2() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part2(&generated), 5031);
     }
+
+    // all 11 cube nets, one char per square
+    const NETS: [&str; 11] = [
+        "#...\n####\n#...",
+        "#...\n####\n.#..",
+        "#...\n####\n..#.",
+        "#...\n####\n...#",
+        ".#..\n####\n.#..",
+        ".#..\n####\n..#.",
+        "##..\n.###\n.#..",
+        "##..\n.###\n..#.",
+        "##..\n.###\n...#",
+        "##..\n.##.\n..##",
+        "###..\n..###",
+    ];
+
+    fn expand(net: &str, k: usize, instructions: &str) -> String {
+        let mut input = String::new();
+        for row in net.lines() {
+            for _ in 0..k {
+                for c in row.chars() {
+                    let fill = if c == '#' { "." } else { " " };
+                    input.push_str(&fill.repeat(k));
+                }
+                input.push('\n');
+            }
+        }
+        input.push('\n');
+        input.push_str(instructions);
+        input
+    }
+
+    #[test]
+    fn check_fold_all_nets() {
+        // walking straight around the cube ends up where it started
+        for net in NETS {
+            for k in [1, 3, 4] {
+                let start = net.find('#').unwrap() * k;
+                let straight = 1000 + 4 * (start as i32 + 1);
+
+                for (turn, facing) in [("", 0), ("0R", 1), ("0R0R", 2), ("0L", 3)] {
+                    let instructions = format!("{turn}{}", 4 * k);
+                    let generated = super::parse(&expand(net, k, &instructions)).unwrap();
+                    assert_eq!(super::solve_part2(&generated), straight + facing, "{net}");
+                }
+            }
+        }
+    }
 }
@@ -1,11 +1,8 @@
-use std::collections::HashSet;
-use std::hash::{Hash, Hasher};
 use std::ops::RangeInclusive;
 
 use itertools::Itertools;
-use itertools::MinMaxResult::MinMax;
 
-use crate::vec::Vec2;
+use crate::{error::ParseError, grid::Grid, vec::Vec2};
 
 const N: Vec2 = Vec2 { x: 0, y: -1 };
 const NE: Vec2 = Vec2 { x: 1, y: -1 };
@@ -23,121 +20,313 @@ const SOUTH: [Vec2; 3] = [S, SE, SW];
 const WEST: [Vec2; 3] = [W, NW, SW];
 const EAST: [Vec2; 3] = [E, NE, SE];
 
+/// A move an elf can propose, if none of the `checks` cells has an elf.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proposal {
+    pub step: Vec2,
+    pub checks: Vec<Vec2>,
+}
+
+/// How the elves spread out: an elf with anyone in its `neighbourhood`
+/// proposes the first possible of the `proposals`, which are rotated by
+/// `rotation` every round.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    neighbourhood: Vec<Vec2>,
+    proposals: Vec<Proposal>,
+    rotation: usize,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        let proposals = [NORTH, SOUTH, WEST, EAST].map(|checks| Proposal {
+            step: checks[0],
+            checks: checks.to_vec(),
+        });
+        Rules::new(ADJ.to_vec(), proposals.to_vec(), 1)
+    }
+}
+
+impl Rules {
+    /// Panics unless every cell looked at is adjacent (including diagonally),
+    /// e.g. the axial coordinates of a hex grid are fine.
+    pub fn new(neighbourhood: Vec<Vec2>, proposals: Vec<Proposal>, rotation: usize) -> Self {
+        let cells = neighbourhood.iter().chain(
+            proposals
+                .iter()
+                .flat_map(|p| p.checks.iter().chain([&p.step])),
+        );
+        for d in cells {
+            assert!(
+                d.chebyshev(Vec2::ZERO) == 1,
+                "rules can only look at adjacent cells, not {d:?}"
+            );
+        }
+
+        Rules {
+            neighbourhood,
+            proposals,
+            rotation,
+        }
+    }
+
+    /// Only ever looking at and moving to the 4 orthogonal neighbours.
+    pub fn orthogonal() -> Self {
+        let proposals = [N, S, W, E].map(|d| Proposal {
+            step: d,
+            checks: vec![d],
+        });
+        Rules::new(vec![N, E, S, W], proposals.to_vec(), 1)
+    }
+
+    // the proposals in the order they are considered in `round` (from 1)
+    fn order(&self, round: usize) -> impl Iterator<Item = &Proposal> {
+        let first = (round - 1) * self.rotation % self.proposals.len().max(1);
+        self.proposals[first..]
+            .iter()
+            .chain(&self.proposals[..first])
+    }
+}
+
+// rows are bitsets of 64 bit words, bit x of a row is the column `left + x`
+#[derive(Clone)]
 pub struct Board {
-    elves: HashSet<Vec2>,
+    bits: Vec<u64>,
+    words: usize,
+    rows: usize,
+    left: i32,
+    top: i32,
 }
 
 impl FromIterator<Vec2> for Board {
     fn from_iter<T: IntoIterator<Item = Vec2>>(iter: T) -> Self {
-        Board {
-            elves: iter.into_iter().collect(),
+        let elves = iter.into_iter().collect_vec();
+        let (left, top) = match (
+            elves.iter().map(|p| p.x).min(),
+            elves.iter().map(|p| p.y).min(),
+        ) {
+            (Some(x), Some(y)) => (x - 1, y - 1),
+            _ => (0, 0),
+        };
+
+        let mut board = Board {
+            bits: vec![0; 1],
+            words: 1,
+            rows: 1,
+            left,
+            top,
+        };
+        for p in elves {
+            board.grow(p);
+            board.set(p);
         }
+        board
     }
 }
 
-impl ToOwned for Board {
-    type Owned = Self;
-    fn to_owned(&self) -> Self::Owned {
-        Board {
-            elves: self.elves.to_owned(),
+impl Board {
+    fn row(&self, y: usize) -> &[u64] {
+        &self.bits[y * self.words..(y + 1) * self.words]
+    }
+
+    fn set(&mut self, p: Vec2) {
+        let (x, y) = ((p.x - self.left) as usize, (p.y - self.top) as usize);
+        self.bits[y * self.words + x / 64] |= 1 << (x % 64);
+    }
+
+    // makes room for `p` with an empty cell all around it
+    fn grow(&mut self, p: Vec2) {
+        while p.y - 1 < self.top {
+            let more = self.rows.max(8);
+            self.bits.splice(0..0, vec![0; more * self.words]);
+            self.rows += more;
+            self.top -= more as i32;
+        }
+        while p.y + 1 >= self.top + self.rows as i32 {
+            let more = self.rows.max(8);
+            self.bits.resize(self.bits.len() + more * self.words, 0);
+            self.rows += more;
+        }
+        while p.x - 1 < self.left || p.x + 1 >= self.left + 64 * self.words as i32 {
+            // widen on the side running out of room
+            let front = p.x - 1 < self.left;
+            let mut bits = Vec::with_capacity(self.rows * (self.words + 1));
+            for y in 0..self.rows {
+                if front {
+                    bits.push(0);
+                }
+                bits.extend_from_slice(self.row(y));
+                if !front {
+                    bits.push(0);
+                }
+            }
+            self.bits = bits;
+            self.words += 1;
+            if front {
+                self.left -= 64;
+            }
         }
     }
-}
 
-impl Board {
+    // keeps an empty border around the elves, so no shift drops any of them
+    fn reserve(&mut self) {
+        let (xs, ys) = self.extent();
+        self.grow(Vec2::new(*xs.start(), *ys.start()));
+        self.grow(Vec2::new(*xs.end(), *ys.end()));
+    }
+
+    // `out` at (x, y) is `src` at (x + d.x, y + d.y), for |d.x| < 64
+    fn shifted(&self, src: &[u64], d: Vec2) -> Vec<u64> {
+        let mut out = vec![0; src.len()];
+        let k = d.x.unsigned_abs();
+        for y in 0..self.rows {
+            let from = y as i64 + d.y as i64;
+            if from < 0 || from >= self.rows as i64 {
+                continue;
+            }
+            let row = &src[from as usize * self.words..][..self.words];
+            let out = &mut out[y * self.words..][..self.words];
+            for (i, out) in out.iter_mut().enumerate() {
+                *out = match d.x {
+                    0 => row[i],
+                    // bits move towards lower columns
+                    1.. => {
+                        let carry = row.get(i + 1).map_or(0, |w| w << (64 - k));
+                        row[i] >> k | carry
+                    }
+                    _ => {
+                        let carry = i.checked_sub(1).map_or(0, |j| row[j] >> (64 - k));
+                        row[i] << k | carry
+                    }
+                };
+            }
+        }
+        out
+    }
+
+    fn len(&self) -> usize {
+        self.bits.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
     fn extent(&self) -> (RangeInclusive<i32>, RangeInclusive<i32>) {
-        let x = self.elves.iter().minmax_by_key(|v| v.x);
-        let y = self.elves.iter().minmax_by_key(|v| v.y);
+        let ys = (0..self.rows).filter(|&y| self.row(y).iter().any(|&w| w != 0));
+        let xs = (0..self.rows).flat_map(|y| {
+            let words = self.row(y).iter().enumerate().filter(|(_, &w)| w != 0);
+            words.flat_map(|(i, w)| {
+                [
+                    64 * i + w.trailing_zeros() as usize,
+                    64 * i + 63 - w.leading_zeros() as usize,
+                ]
+            })
+        });
 
-        match (x, y) {
-            (MinMax(xmin, xmax), MinMax(ymin, ymax)) => (xmin.x..=xmax.x, ymin.y..=ymax.y),
+        match (xs.minmax().into_option(), ys.minmax().into_option()) {
+            (Some((xmin, xmax)), Some((ymin, ymax))) => (
+                self.left + xmin as i32..=self.left + xmax as i32,
+                self.top + ymin as i32..=self.top + ymax as i32,
+            ),
             _ => unreachable!(),
         }
     }
-}
 
-#[derive(Copy, Clone)]
-struct Update {
-    old: Vec2,
-    new: Vec2,
-}
-
-impl Hash for Update {
-    // only hash the new position - so Iterator::counts() can be used below
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.new.hash(state)
+    /// Every elf, row by row.
+    pub fn elves(&self) -> impl Iterator<Item = Vec2> + '_ {
+        (0..self.rows).flat_map(move |y| {
+            (0..64 * self.words)
+                .filter(move |x| self.row(y)[x / 64] & 1 << (x % 64) != 0)
+                .map(move |x| Vec2::new(self.left + x as i32, self.top + y as i32))
+        })
     }
-}
 
-impl PartialEq for Update {
-    fn eq(&self, other: &Self) -> bool {
-        self.new == other.new
+    // one round with the proposals in `order`, returns how many elves moved
+    fn round<'a>(
+        &mut self,
+        neighbourhood: &[Vec2],
+        order: impl Iterator<Item = &'a Proposal>,
+    ) -> usize {
+        self.reserve();
+        let or = |a: &mut [u64], b: &[u64]| a.iter_mut().zip(b).for_each(|(a, b)| *a |= b);
+        let andnot = |a: &mut [u64], b: &[u64]| a.iter_mut().zip(b).for_each(|(a, b)| *a &= !b);
+
+        let neighbors = neighbourhood
+            .iter()
+            .map(|&d| (d, self.shifted(&self.bits, d)))
+            .collect_vec();
+
+        // elves with someone around them
+        let mut undecided = vec![0; self.bits.len()];
+        for (_, n) in &neighbors {
+            or(&mut undecided, n);
+        }
+        for (u, b) in undecided.iter_mut().zip(&self.bits) {
+            *u &= b;
+        }
+
+        // the first free direction of every elf, and which targets are
+        // proposed at least once and more than once
+        let mut proposals = vec![];
+        let mut once = vec![0; self.bits.len()];
+        let mut twice = vec![0; self.bits.len()];
+        for proposal in order {
+            let mut proposing = undecided.clone();
+            for &d in &proposal.checks {
+                andnot(&mut proposing, &self.shifted(&self.bits, d));
+            }
+            andnot(&mut undecided, &proposing);
+
+            let target = self.shifted(&proposing, -proposal.step);
+            for ((o, t), n) in once.iter_mut().zip(&mut twice).zip(&target) {
+                *t |= *o & n;
+                *o |= n;
+            }
+            proposals.push((proposal.step, proposing));
+        }
+
+        let mut moved = 0;
+        let mut bits = self.bits.clone();
+        for (d, mut proposing) in proposals {
+            // nobody moves into a cell proposed twice
+            andnot(&mut proposing, &self.shifted(&twice, d));
+            moved += proposing
+                .iter()
+                .map(|w| w.count_ones() as usize)
+                .sum::<usize>();
+
+            andnot(&mut bits, &proposing);
+            or(&mut bits, &self.shifted(&proposing, -d));
+        }
+        self.bits = bits;
+        moved
     }
 }
-impl Eq for Update {}
 
 #[aoc_generator(day23)]
-pub fn parse(input: &str) -> Board {
-    input
-        .lines()
-        .enumerate()
-        .flat_map(|(y, l)| {
-            l.chars()
-                .enumerate()
-                .filter_map(move |(x, c)| match c {
-                    '#' => Some(Vec2 {
-                        x: x as i32,
-                        y: y as i32,
-                    }),
-                    _ => None,
-                })
-                .collect_vec()
-        })
-        .collect()
+pub fn parse(input: &str) -> Result<Board, ParseError> {
+    let grid = Grid::parse(input, "'#' or '.'", |c| match c {
+        '#' => Some(true),
+        '.' => Some(false),
+        _ => None,
+    })?;
+    let board: Board = grid
+        .iter()
+        .filter(|(_, &elf)| elf)
+        .map(|(p, _)| p)
+        .collect();
+    if board.len() == 0 {
+        let eof = grid.height() + 1;
+        return Err(ParseError::end(eof, "", "couldn't find any elf"));
+    }
+    Ok(board)
 }
 
-fn diffuse<F>(board: &mut Board, termination: F) -> usize
+/// Spreads the elves out until `termination` (given the round and how many
+/// elves moved in it) holds, returns the last round.
+pub fn diffuse<F>(board: &mut Board, rules: &Rules, termination: F) -> usize
 where
     F: Fn(usize, usize) -> bool,
 {
-    let mut cyclic = [NORTH, SOUTH, WEST, EAST, NORTH, SOUTH, WEST]
-        .array_windows::<4>()
-        .cycle();
-
     for i in 1.. {
-        let order = cyclic.next().unwrap();
-        let num_updates = board
-            .elves
-            .iter()
-            .filter(|&p| ADJ.iter().any(|&dp| board.elves.contains(&(*p + dp))))
-            .filter_map(|p| {
-                order.iter().find_map(|check| {
-                    if check.iter().all(|&dp| !board.elves.contains(&(*p + dp))) {
-                        Some(Update {
-                            old: *p,
-                            new: *p + check[0],
-                        })
-                    } else {
-                        None
-                    }
-                })
-            })
-            .counts()
-            .iter()
-            .filter_map(
-                |(&update, &count)| {
-                    if count == 1 {
-                        Some(update)
-                    } else {
-                        None
-                    }
-                },
-            )
-            .map(|Update { old, new }| {
-                board.elves.remove(&old);
-                board.elves.insert(new);
-            })
-            .count();
+        let num_updates = board.round(&rules.neighbourhood, rules.order(i));
 
         if termination(i, num_updates) {
             return i;
@@ -148,20 +337,27 @@ where
 
 #[aoc(day23, part1)]
 pub fn solve_part1(input: &Board) -> usize {
-    let mut board = input.to_owned();
-    diffuse(&mut board, |round, _| round == 10);
+    let mut board = input.clone();
+    diffuse(&mut board, &Rules::default(), |round, _| round == 10);
     let (xs, ys) = board.extent();
-    xs.count() * ys.count() - board.elves.len()
+    xs.count() * ys.count() - board.len()
 }
 
 #[aoc(day23, part2)]
 pub fn solve_part2(input: &Board) -> usize {
-    let mut board = input.to_owned();
-    diffuse(&mut board, |_, num_updates| num_updates == 0)
+    let mut board = input.clone();
+    diffuse(&mut board, &Rules::default(), |_, num_updates| {
+        num_updates == 0
+    })
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use super::{Proposal, Rules};
+    use crate::vec::Vec2;
+
     const EXAMPLE: &str = "..............
 ..............
 .......#......
@@ -175,15 +371,150 @@ mod tests {
 ..............
 ..............";
 
+    #[test]
+    fn check_malformed() {
+        let err = super::parse("..\n.x").err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 2: expected '#' or '.', found `x`"
+        );
+
+        let err = super::parse("...\n...").err().unwrap();
+        assert_eq!(err.to_string(), "line 3, column 1: couldn't find any elf");
+    }
+
     #[test]
     fn check_part1() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part1(&generated), 110);
     }
 
     #[test]
     fn check_part2() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part2(&generated), 20);
     }
+
+    #[test]
+    fn check_small() {
+        let mut board = super::parse(
+            ".....
+..##.
+..#..
+.....
+..##.
+.....",
+        )
+        .unwrap();
+        super::diffuse(&mut board, &super::Rules::default(), |round, _| round == 3);
+
+        let elves = board.elves().map(|p| (p.x, p.y)).collect::<Vec<_>>();
+        assert_eq!(elves, [(2, 0), (4, 1), (0, 2), (4, 3), (2, 5)]);
+    }
+
+    #[test]
+    fn check_growth() {
+        // two far apart copies spread over several words in every direction
+        let generated = super::parse(EXAMPLE).unwrap();
+        let mut board = generated
+            .elves()
+            .flat_map(|p| [p, p + Vec2::new(150, -90)])
+            .collect::<super::Board>();
+        assert_eq!(board.len(), 44);
+
+        super::diffuse(&mut board, &super::Rules::default(), |round, _| round == 10);
+        let (xs, ys) = board.extent();
+        assert_eq!(board.len(), 44);
+        assert_eq!(xs.count() * ys.count(), (150 + 12) * (90 + 11));
+    }
+
+    // straight from the puzzle text, one elf at a time
+    fn naive(elves: &mut HashSet<Vec2>, rules: &Rules, round: usize) -> usize {
+        let mut proposed = HashMap::<Vec2, Vec<Vec2>>::new();
+        for &p in elves.iter() {
+            if rules
+                .neighbourhood
+                .iter()
+                .all(|&d| !elves.contains(&(p + d)))
+            {
+                continue;
+            }
+            let free =
+                |proposal: &&Proposal| proposal.checks.iter().all(|&d| !elves.contains(&(p + d)));
+            if let Some(proposal) = rules.order(round).find(free) {
+                proposed.entry(p + proposal.step).or_default().push(p);
+            }
+        }
+
+        let mut moved = 0;
+        for (to, from) in proposed {
+            if let [from] = from[..] {
+                elves.remove(&from);
+                elves.insert(to);
+                moved += 1;
+            }
+        }
+        moved
+    }
+
+    #[test]
+    fn check_rules() {
+        let hex = [(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)].map(Vec2::from);
+        let variants = [
+            Rules::default(),
+            Rules::orthogonal(),
+            // a hex grid in axial coordinates, checking either side of a move
+            Rules::new(
+                hex.to_vec(),
+                (0..6)
+                    .map(|i| Proposal {
+                        step: hex[i],
+                        checks: vec![hex[(i + 5) % 6], hex[i], hex[(i + 1) % 6]],
+                    })
+                    .collect(),
+                2,
+            ),
+            // the puzzle rules without rotating
+            Rules {
+                rotation: 0,
+                ..Rules::default()
+            },
+        ];
+
+        let mut seed: u64 = 0x2022_1223;
+        for rules in &variants {
+            let start = (0..400)
+                .filter_map(|_| {
+                    seed ^= seed << 13;
+                    seed ^= seed >> 7;
+                    seed ^= seed << 17;
+                    let p = Vec2::new((seed % 20) as i32, (seed / 20 % 20) as i32);
+                    (seed >> 40 & 1 == 0).then_some(p)
+                })
+                .collect::<HashSet<_>>();
+
+            let mut board = start.iter().copied().collect::<super::Board>();
+            let mut elves = start;
+            for round in 1..=30 {
+                let expected = naive(&mut elves, rules, round);
+                let moved = board.round(&rules.neighbourhood, rules.order(round));
+                assert_eq!(moved, expected, "round {round} of {rules:?}");
+                assert_eq!(board.elves().collect::<HashSet<_>>(), elves);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "adjacent")]
+    fn check_far_rule() {
+        let far = Vec2::new(0, -2);
+        Rules::new(
+            vec![far],
+            vec![Proposal {
+                step: far,
+                checks: vec![far],
+            }],
+            1,
+        );
+    }
 }
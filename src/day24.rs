@@ -1,12 +1,7 @@
-use std::{
-    collections::{HashMap, HashSet},
-    fmt::Display,
-};
+use std::fmt::Display;
 
-use crate::vec::Vec2;
-use itertools::Itertools;
+use crate::{error::ParseError, grid::Grid, search, vec::Vec2};
 use num::integer::lcm;
-use rayon::prelude::*;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 enum Direction {
@@ -42,7 +37,12 @@ impl Display for Direction {
     }
 }
 
-type Blizzard = HashMap<Vec2, Vec<Direction>>;
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Down,
+    Direction::Left,
+    Direction::Right,
+];
 
 pub struct Basin {
     start: Vec2,
@@ -51,10 +51,34 @@ pub struct Basin {
     width: usize,
     height: usize,
 
-    blizzard: Blizzard,
+    // where the blizzards are at minute 0
+    blizzards: Grid<Option<Direction>>,
+}
+
+/// One leg of a `Trip`, `route` holds the position at every minute of it
+/// (both ends included).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Leg {
+    pub minutes: usize,
+    pub route: Vec<Vec2>,
+}
+
+/// The quickest trip visiting the waypoints in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trip {
+    pub minutes: usize,
+    pub legs: Vec<Leg>,
 }
 
 impl Basin {
+    pub fn start(&self) -> Vec2 {
+        self.start
+    }
+
+    pub fn end(&self) -> Vec2 {
+        self.end
+    }
+
     fn wall(&self, p: &Vec2) -> bool {
         if *p == self.start || *p == self.end {
             return false;
@@ -68,153 +92,212 @@ impl Basin {
         false
     }
 
-    fn blizzard_at(&self, k: usize) -> Blizzard {
-        self.blizzard
+    // a blizzard only ever moves along its row or column, so at minute `k`
+    // the one heading `d` in `p` started `k` steps back (with wrap around)
+    fn blows(&self, p: Vec2, k: usize, d: Direction) -> bool {
+        let (w, h) = (self.width as i32 - 2, self.height as i32 - 2);
+        let k = (k % self.period()) as i32;
+        let dp: Vec2 = d.into();
+        let from = p - Vec2 { x: 1, y: 1 } - dp * k;
+        let from = Vec2 {
+            x: from.x.rem_euclid(w) + 1,
+            y: from.y.rem_euclid(h) + 1,
+        };
+        self.blizzards[from] == Some(d)
+    }
+
+    fn inside(&self, p: Vec2) -> bool {
+        !self.wall(&p) && p != self.start && p != self.end
+    }
+
+    fn blizzards_at(&self, p: Vec2, k: usize) -> Vec<Direction> {
+        if !self.inside(p) {
+            return vec![];
+        }
+        DIRECTIONS
+            .into_iter()
+            .filter(|&d| self.blows(p, k, d))
+            .collect()
+    }
+
+    fn blizzard(&self, p: Vec2, k: usize) -> bool {
+        self.inside(p) && DIRECTIONS.into_iter().any(|d| self.blows(p, k, d))
+    }
+
+    /// The basin at `minute` like in the puzzle, with the number of blizzards
+    /// where they overlap and the expedition as `E`.
+    pub fn render(&self, minute: usize, expedition: Option<Vec2>) -> String {
+        let mut out = String::new();
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                let p = Vec2::new(x, y);
+                let blizzards = self.blizzards_at(p, minute);
+                let cell = match blizzards[..] {
+                    _ if Some(p) == expedition => "E".to_owned(),
+                    _ if self.wall(&p) => "#".to_owned(),
+                    [] => ".".to_owned(),
+                    [d] => d.to_string(),
+                    _ => blizzards.len().to_string(),
+                };
+                out += &cell;
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Every minute of `trip` rendered with a heading like in the puzzle.
+    pub fn replay(&self, trip: &Trip) -> String {
+        // every leg starts where the previous one ended
+        let route = trip
+            .legs
             .iter()
-            .flat_map(move |(&p, ds)| {
-                ds.iter().map(move |&d| {
-                    let dp: Vec2 = d.into();
-                    let mut p = p - Vec2 { x: 1, y: 1 };
-                    p += dp * k as i32;
-                    p.x = p.x.rem_euclid(self.width as i32 - 2) + 1;
-                    p.y = p.y.rem_euclid(self.height as i32 - 2) + 1;
-                    (p, d)
-                })
-            })
-            .fold(HashMap::<Vec2, Vec<Direction>>::new(), |mut map, (p, d)| {
-                if let Some(ds) = map.get_mut(&p) {
-                    ds.push(d);
-                } else {
-                    map.insert(p, vec![d]);
+            .enumerate()
+            .flat_map(|(i, leg)| &leg.route[(i > 0) as usize..])
+            .collect::<Vec<_>>();
+
+        let mut out = String::new();
+        for (minute, p) in route.iter().enumerate() {
+            let heading = match minute {
+                0 => "Initial state".to_owned(),
+                _ => {
+                    let step = match **p - *route[minute - 1] {
+                        d if d == Vec2::UP => "move up",
+                        d if d == Vec2::DOWN => "move down",
+                        d if d == Vec2::LEFT => "move left",
+                        d if d == Vec2::RIGHT => "move right",
+                        _ => "wait",
+                    };
+                    format!("Minute {minute}, {step}")
                 }
-                map
-            })
+            };
+            if minute > 0 {
+                out.push('\n');
+            }
+            out += &format!("{heading}:\n{}", self.render(minute, Some(**p)));
+        }
+        out
+    }
+
+    // the blizzards repeat after this many minutes
+    fn period(&self) -> usize {
+        lcm(self.width - 2, self.height - 2)
+    }
+
+    // quickest way from `from` to `to` when setting off at minute `minute`
+    fn leg(&self, from: Vec2, to: Vec2, minute: usize) -> Option<Leg> {
+        let k = self.period();
+
+        // the blizzards repeat, so the minute modulo k is enough state
+        let successors = |&(p, i): &(Vec2, usize)| {
+            // adjacent moves or wait
+            DIRECTIONS
+                .into_iter()
+                .map(move |adj| p + adj.into())
+                .chain([p])
+                .filter(move |next| *next == p || !self.wall(next))
+                .filter(move |&next| !self.blizzard(next, i + 1))
+                .map(move |next| (next, (i + 1) % k))
+        };
+
+        let path = search::bfs((from, minute % k), successors, |&(p, _)| p == to)?;
+        Some(Leg {
+            minutes: path.cost,
+            route: path.nodes.into_iter().map(|(p, _)| p).collect(),
+        })
+    }
+
+    /// Plans a trip through `waypoints` setting off from the first one at
+    /// minute 0, `None` if a waypoint is in a wall or can't be reached.
+    pub fn plan(&self, waypoints: &[Vec2]) -> Option<Trip> {
+        if waypoints.iter().any(|p| self.wall(p)) {
+            return None;
+        }
+
+        let mut trip = Trip {
+            minutes: 0,
+            legs: vec![],
+        };
+        for w in waypoints.windows(2) {
+            let leg = self.leg(w[0], w[1], trip.minutes)?;
+            trip.minutes += leg.minutes;
+            trip.legs.push(leg);
+        }
+        Some(trip)
     }
 }
 
 #[aoc_generator(day24)]
-pub fn parse(input: &str) -> Basin {
-    let lines = input.lines().collect_vec();
-    let width = lines[0].len();
-    let height = lines.len();
+pub fn parse(input: &str) -> Result<Basin, ParseError> {
+    let grid = Grid::parse(input, "a wall, ground or blizzard", |c| {
+        "#.^v<>".contains(c).then_some(c)
+    })?;
+    let width = grid.width();
+    let height = grid.height();
+    if height < 3 {
+        return Err(ParseError::end(height + 1, "", "expected at least 3 rows"));
+    }
+    if width < 3 {
+        let l = input.lines().next().unwrap_or_default();
+        return Err(ParseError::line(1, l, "expected at least 3 columns"));
+    }
+
+    let opening = |y: usize| {
+        grid.row(y).iter().position(|&c| c == '.').ok_or_else(|| {
+            let l = input.lines().nth(y).unwrap_or_default();
+            ParseError::line(y + 1, l, "expected an opening")
+        })
+    };
 
     let start = Vec2 {
-        x: lines[0]
-            .chars()
-            .enumerate()
-            .find_map(|(x, c)| if c == '.' { Some(x) } else { None })
-            .unwrap() as i32,
+        x: opening(0)? as i32,
         y: 0,
     };
 
     let end = Vec2 {
-        x: lines[height - 1]
-            .chars()
-            .enumerate()
-            .find_map(|(x, c)| if c == '.' { Some(x) } else { None })
-            .unwrap() as i32,
+        x: opening(height - 1)? as i32,
         y: height as i32 - 1,
     };
 
-    let blizzard = lines
-        .iter()
-        .enumerate()
-        .flat_map(|(y, l)| {
-            l.chars().enumerate().filter_map(move |(x, c)| {
-                let p = Vec2 {
-                    x: x as i32,
-                    y: y as i32,
-                };
-                match c {
-                    '^' => Some((p, vec![Direction::Up])),
-                    'v' => Some((p, vec![Direction::Down])),
-                    '>' => Some((p, vec![Direction::Right])),
-                    '<' => Some((p, vec![Direction::Left])),
-                    _ => None,
-                }
-            })
-        })
-        .collect::<HashMap<_, _>>();
+    let blizzards = grid.map(|c| match c {
+        '^' => Some(Direction::Up),
+        'v' => Some(Direction::Down),
+        '>' => Some(Direction::Right),
+        '<' => Some(Direction::Left),
+        _ => None,
+    });
 
-    Basin {
+    Ok(Basin {
         start,
         end,
         width,
         height,
-        blizzard,
-    }
+        blizzards,
+    })
 }
 
-fn solve(
-    start: &Vec2,
-    end: &Vec2,
-    basin: &Basin,
-    blizzard: usize,
-    blizzards: &[Blizzard],
-) -> usize {
-    let k = blizzards.len();
-
-    let mut positions = HashSet::new();
-    positions.insert(*start);
-
-    for i in blizzard.. {
-        let mut next_positions = HashSet::new();
-        for p in positions.iter() {
-            if p == end {
-                return i - blizzard;
-            }
-
-            let next_blizzard = &blizzards[(i + 1) % k];
-
-            // adjacent moves
-            for adj in [
-                Direction::Up,
-                Direction::Down,
-                Direction::Left,
-                Direction::Right,
-            ] {
-                let next = *p + adj.into();
-                if !basin.wall(&next) && !next_blizzard.contains_key(&next) {
-                    next_positions.insert(next);
-                }
-            }
-
-            // wait
-            if !next_blizzard.contains_key(p) {
-                next_positions.insert(*p);
-            }
-        }
-        positions = next_positions;
-    }
-    unreachable!()
+fn trip_minutes(basin: &Basin, waypoints: &[Vec2]) -> usize {
+    basin
+        .plan(waypoints)
+        .expect("no way through the blizzards")
+        .minutes
 }
 
 #[aoc(day24, part1)]
 pub fn solve_part1(basin: &Basin) -> usize {
-    let num_blizzards = lcm(basin.width - 2, basin.height - 2);
-    let blizzards: Vec<Blizzard> = (0..num_blizzards)
-        .into_par_iter()
-        .map(|k| basin.blizzard_at(k))
-        .collect();
-    solve(&basin.start, &basin.end, basin, 0, &blizzards)
+    trip_minutes(basin, &[basin.start, basin.end])
 }
 
 #[aoc(day24, part2)]
 pub fn solve_part2(basin: &Basin) -> usize {
-    let num_blizzards = lcm(basin.width - 2, basin.height - 2);
-    let blizzards: Vec<Blizzard> = (0..num_blizzards)
-        .into_par_iter()
-        .map(|k| basin.blizzard_at(k))
-        .collect();
-
-    let a = solve(&basin.start, &basin.end, basin, 0, &blizzards);
-    let b = solve(&basin.end, &basin.start, basin, a, &blizzards);
-    let c = solve(&basin.start, &basin.end, basin, a + b, &blizzards);
-    a + b + c
+    // back for the snacks
+    trip_minutes(basin, &[basin.start, basin.end, basin.start, basin.end])
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::vec::Vec2;
+
     const EXAMPLE: &str = "#.######
 #>>.<^<#
 #.<..<<#
@@ -222,15 +305,136 @@ mod tests {
 #<^v^^>#
 ######.#";
 
+    #[test]
+    fn check_malformed() {
+        let err = super::parse("#.#\n#.#").err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 3, column 1: expected at least 3 rows"
+        );
+
+        // no room for blizzards to blow between the walls
+        let err = super::parse("..\n..\n..").err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 1, column 1: expected at least 3 columns, found `..`"
+        );
+    }
+
     #[test]
     fn check_part1() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part1(&generated), 18);
     }
 
     #[test]
     fn check_part2() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part2(&generated), 54);
     }
+
+    #[test]
+    fn check_blizzard() {
+        // the simple example from the puzzle, at minute 3 both blizzards
+        // are in the same spot
+        let basin = super::parse(
+            "#.#####
+#.....#
+#>....#
+#.....#
+#...v.#
+#.....#
+#####.#",
+        )
+        .unwrap();
+        let at = |x, y, k| basin.blizzard(Vec2::new(x, y), k);
+
+        assert!(at(1, 2, 0) && at(4, 4, 0));
+        assert!(at(2, 2, 1) && at(4, 5, 1));
+        assert!(at(4, 2, 3) && !at(4, 4, 3));
+        assert!(at(1, 2, 5) && at(4, 1, 2));
+        assert!(at(4, 2, 3 + basin.period()));
+        assert!(!at(1, 0, 0) && !at(0, 2, 0));
+    }
+
+    #[test]
+    fn check_non_coprime() {
+        // 4 by 6 inside, the blizzards repeat every 12 minutes
+        let generated = super::parse(
+            "#.####
+#>.<.#
+#.^..#
+#..v.#
+#<...#
+#.>..#
+#....#
+####.#",
+        )
+        .unwrap();
+        assert_eq!(generated.period(), 12);
+        assert_eq!(super::solve_part1(&generated), 10);
+    }
+
+    #[test]
+    fn check_plan() {
+        let basin = super::parse(EXAMPLE).unwrap();
+        let (start, end) = (basin.start(), basin.end());
+
+        let trip = basin.plan(&[start, end, start, end]).unwrap();
+        assert_eq!(trip.minutes, 54);
+        assert_eq!(
+            trip.legs.iter().map(|l| l.minutes).collect::<Vec<_>>(),
+            [18, 23, 13]
+        );
+
+        let mut minute = 0;
+        for leg in &trip.legs {
+            assert_eq!(leg.route.len(), leg.minutes + 1);
+            for (i, w) in leg.route.windows(2).enumerate() {
+                assert!(w[0].manhattan(w[1]) <= 1);
+                assert!(!basin.blizzard(w[1], minute + i + 1));
+            }
+            minute += leg.minutes;
+        }
+
+        // detour through the middle of the basin
+        let detour = basin.plan(&[start, Vec2::new(3, 2), end]).unwrap();
+        assert_eq!(detour.legs[0].route.last(), Some(&Vec2::new(3, 2)));
+        assert!(detour.minutes >= 18);
+
+        assert_eq!(basin.plan(&[start]).unwrap().minutes, 0);
+        assert_eq!(basin.plan(&[start, Vec2::new(0, 1)]), None);
+    }
+
+    #[test]
+    fn check_render() {
+        let basin = super::parse(EXAMPLE).unwrap();
+        assert_eq!(basin.render(0, None), format!("{EXAMPLE}\n"));
+        assert_eq!(
+            basin.render(1, Some(Vec2::new(1, 1))),
+            "#.######
+#E>3.<.#
+#<..<<.#
+#>2.22.#
+#>v..^<#
+######.#
+"
+        );
+        assert_eq!(
+            basin.render(18, Some(basin.end())),
+            "#.######
+#>2.<.<#
+#.2v^2<#
+#>..>2>#
+#<....>#
+######E#
+"
+        );
+
+        let trip = basin.plan(&[basin.start(), basin.end()]).unwrap();
+        let replay = basin.replay(&trip);
+        assert!(replay.starts_with("Initial state:\n#E######\n"));
+        assert!(replay.contains("\n\nMinute 18, move down:\n"));
+        assert_eq!(replay.matches("Minute").count(), 18);
+    }
 }
@@ -1,6 +1,8 @@
 use itertools::Itertools;
 use take_until::*;
 
+use crate::error::{lines, ParseError};
+
 fn from_snafu(snafu: &str) -> i64 {
     snafu
         .chars()
@@ -66,8 +68,13 @@ fn to_snafu(n: i64) -> String {
 }
 
 #[aoc_generator(day25)]
-pub fn parse(input: &str) -> Vec<String> {
-    input.lines().map(String::from).collect()
+pub fn parse(input: &str) -> Result<Vec<String>, ParseError> {
+    lines(input)
+        .map(|(n, l)| match l.matches(|c| !"=-012".contains(c)).next() {
+            Some(c) => Err(ParseError::token(n, l, c, "expected a SNAFU digit")),
+            None => Ok(l.to_owned()),
+        })
+        .collect()
 }
 
 #[aoc(day25, part1)]
@@ -102,13 +109,13 @@ mod tests {
 
     #[test]
     fn check_part1() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part1(&generated), "2=-1=0");
     }
 
     #[test]
     fn check_snafu() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         let decode_encode = generated
             .iter()
             .map(|s| super::from_snafu(s))
@@ -119,7 +126,7 @@ mod tests {
 
     #[test]
     fn check_part2() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part2(&generated), 0);
     }
 }
@@ -1,5 +1,7 @@
 use itertools::Itertools;
 
+use crate::error::{lines, ParseError};
+
 type Compartments = (String, String);
 
 pub fn get_priority(c: char) -> u32 {
@@ -7,12 +9,17 @@ pub fn get_priority(c: char) -> u32 {
 }
 
 #[aoc_generator(day3)]
-pub fn parse_rucksack(input: &str) -> Vec<Compartments> {
-    input
-        .lines()
-        .map(|l| {
+pub fn parse_rucksack(input: &str) -> Result<Vec<Compartments>, ParseError> {
+    lines(input)
+        .map(|(n, l)| {
+            if let Some(c) = l.matches(|c: char| !c.is_ascii_alphabetic()).next() {
+                return Err(ParseError::token(n, l, c, "expected an item letter"));
+            }
+            if l.len() % 2 != 0 {
+                return Err(ParseError::line(n, l, "expected an even number of items"));
+            }
             let mid = l.len() / 2;
-            (l[..mid].to_owned(), l[mid..].to_owned())
+            Ok((l[..mid].to_owned(), l[mid..].to_owned()))
         })
         .collect()
 }
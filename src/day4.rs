@@ -1,17 +1,14 @@
 use std::ops::RangeInclusive;
 
-use itertools::Itertools;
+use crate::error::{lines, number, ParseError};
 
 type Assignments = (RangeInclusive<u32>, RangeInclusive<u32>);
 
-fn parse_range(range_str: &str) -> RangeInclusive<u32> {
-    range_str
-        .split('-')
-        .take(2)
-        .filter_map(|s| s.parse::<u32>().ok())
-        .next_tuple()
-        .map(|(a, b)| a..=b)
-        .unwrap()
+fn parse_range(n: usize, line: &str, range_str: &str) -> Result<RangeInclusive<u32>, ParseError> {
+    let (a, b) = range_str
+        .split_once('-')
+        .ok_or_else(|| ParseError::token(n, line, range_str, "expected a range a-b"))?;
+    Ok(number(n, line, a)?..=number(n, line, b)?)
 }
 
 fn range_contained(r1: &RangeInclusive<u32>, r2: &RangeInclusive<u32>) -> bool {
@@ -23,15 +20,13 @@ fn range_overlaps(r1: &RangeInclusive<u32>, r2: &RangeInclusive<u32>) -> bool {
 }
 
 #[aoc_generator(day4)]
-pub fn parse_assignments(input: &str) -> Vec<Assignments> {
-    input
-        .lines()
-        .map(|l| {
-            l.split(',')
-                .take(2)
-                .map(parse_range)
-                .collect_tuple()
-                .unwrap()
+pub fn parse_assignments(input: &str) -> Result<Vec<Assignments>, ParseError> {
+    lines(input)
+        .map(|(n, l)| {
+            let (a, b) = l
+                .split_once(',')
+                .ok_or_else(|| ParseError::line(n, l, "expected two ranges"))?;
+            Ok((parse_range(n, l, a)?, parse_range(n, l, b)?))
         })
         .collect()
 }
@@ -2,51 +2,96 @@ use std::collections::VecDeque;
 
 use itertools::Itertools;
 
+use crate::error::{lines, number, ParseError};
+
 type Stack = VecDeque<char>;
 type Move = (usize, usize, usize);
 
 type Crates = (Vec<Stack>, Vec<Move>);
 
-fn parse_supply_stacks<'a, L>(stacks: L) -> Vec<Stack>
+fn parse_supply_stacks<'a, L>(stacks: L, eof: usize) -> Result<Vec<Stack>, ParseError>
 where
-    L: Iterator<Item = &'a str>,
+    L: Iterator<Item = (usize, &'a str)>,
 {
-    let mut parsed = vec![VecDeque::new(); 9];
+    let mut stacks = stacks.collect_vec();
+
+    // the stacks are numbered 1, 2, .. under the crates
+    let (n, l) = stacks
+        .pop()
+        .ok_or_else(|| ParseError::end(eof, "", "expected the stacks"))?;
+    for (k, label) in l.split_whitespace().enumerate() {
+        if number::<usize>(n, l, label)? != k + 1 {
+            return Err(ParseError::token(
+                n,
+                l,
+                label,
+                format!("expected stack {}", k + 1),
+            ));
+        }
+    }
+    let mut parsed = vec![VecDeque::new(); l.split_whitespace().count()];
 
-    stacks.for_each(|l| {
-        l.char_indices().for_each(|(i, c)| {
+    for (n, l) in stacks {
+        for (i, c) in l.char_indices() {
             if i % 4 == 1 && c != ' ' {
                 // all crates satisfy this
-                parsed[i / 4].push_back(c)
+                parsed
+                    .get_mut(i / 4)
+                    .ok_or_else(|| ParseError::token(n, l, &l[i..=i], "too many stacks"))?
+                    .push_back(c)
             }
-        })
-    });
+        }
+    }
 
-    parsed
+    Ok(parsed)
 }
 
-fn parse_move(move_str: &str) -> Move {
-    move_str
-        .split(' ')
-        .filter_map(|s| s.parse::<usize>().ok())
-        .next_tuple()
-        .unwrap()
+// `heights` are the stack sizes before the move, and after it once it fits
+fn parse_move(n: usize, move_str: &str, heights: &mut [usize]) -> Result<Move, ParseError> {
+    let stack = |s| match number::<usize>(n, move_str, s)? {
+        k @ 1.. if k <= heights.len() => Ok(k),
+        _ => Err(ParseError::token(n, move_str, s, "no such stack")),
+    };
+
+    let (k, a, b) = match move_str.split(' ').collect_vec()[..] {
+        ["move", k, "from", a, "to", b] => (k, stack(a)?, stack(b)?),
+        _ => {
+            return Err(ParseError::line(
+                n,
+                move_str,
+                "expected `move n from a to b`",
+            ))
+        }
+    };
+    let crates = number(n, move_str, k)?;
+    if crates > heights[a - 1] {
+        let message = format!("only {} crates on stack {a}", heights[a - 1]);
+        return Err(ParseError::token(n, move_str, k, message));
+    }
+    heights[a - 1] -= crates;
+    heights[b - 1] += crates;
+
+    Ok((crates, a, b))
 }
 
 #[aoc_generator(day5)]
-pub fn parse_cargo(input: &str) -> Crates {
-    let mut lines = input.lines();
+pub fn parse_cargo(input: &str) -> Result<Crates, ParseError> {
+    let eof = input.lines().count() + 1;
+    let mut lines = lines(input);
 
-    let stack_strs = lines.take_while_ref(|&l| !l.is_empty());
+    let stack_strs = lines.take_while_ref(|&(_, l)| !l.is_empty());
 
-    let stacks = parse_supply_stacks(stack_strs);
+    let stacks = parse_supply_stacks(stack_strs, eof)?;
 
     // skip over empty line
     lines.next();
 
-    let moves = lines.map(parse_move).collect();
+    let mut heights = stacks.iter().map(|s| s.len()).collect_vec();
+    let moves = lines
+        .map(|(n, l)| parse_move(n, l, &mut heights))
+        .collect::<Result<_, _>>()?;
 
-    (stacks, moves)
+    Ok((stacks, moves))
 }
 
 #[aoc(day5, part1)]
@@ -60,7 +105,7 @@ pub fn solve_part1((stacks, moves): &Crates) -> String {
         }
     }
 
-    stacks.iter().map(|s| s.front().unwrap()).collect()
+    stacks.iter().filter_map(|s| s.front()).collect()
 }
 
 #[aoc(day5, part2)]
@@ -75,5 +120,57 @@ pub fn solve_part2((stacks, moves): &Crates) -> String {
             .for_each(|c| stacks[b - 1].push_front(*c));
     }
 
-    stacks.iter().map(|s| s.front().unwrap()).collect()
+    stacks.iter().filter_map(|s| s.front()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    const EXAMPLE: &str = "    [D]    
+[N] [C]    
+[Z] [M] [P]
+ 1   2   3 
+
+move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 2 to 1
+move 1 from 1 to 2";
+
+    #[test]
+    fn check_malformed() {
+        let err = super::parse_cargo(&EXAMPLE.replace("move 2", "move 3"))
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 8, column 6: only 2 crates on stack 2, found `3`"
+        );
+
+        let err = super::parse_cargo(&EXAMPLE.replace("to 3", "to 4"))
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 7, column 18: no such stack, found `4`"
+        );
+
+        let err = super::parse_cargo(&EXAMPLE.replace(" 3 \n", " 4 \n"))
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 4, column 10: expected stack 3, found `4`"
+        );
+    }
+
+    #[test]
+    fn check_part1() {
+        let generated = super::parse_cargo(EXAMPLE).unwrap();
+        assert_eq!(super::solve_part1(&generated), "CMZ");
+    }
+
+    #[test]
+    fn check_part2() {
+        let generated = super::parse_cargo(EXAMPLE).unwrap();
+        assert_eq!(super::solve_part2(&generated), "MCD");
+    }
 }
@@ -1,6 +1,13 @@
+use crate::error::{lines, ParseError};
+
 #[aoc_generator(day6)]
-pub fn parse(input: &str) -> String {
-    input.into()
+pub fn parse(input: &str) -> Result<String, ParseError> {
+    for (n, l) in lines(input) {
+        if let Some(c) = l.matches(|c: char| !c.is_ascii_lowercase()).next() {
+            return Err(ParseError::token(n, l, c, "expected a lowercase letter"));
+        }
+    }
+    Ok(input.into())
 }
 
 fn find_marker(k: usize, stream: &String) -> usize {
@@ -49,7 +56,7 @@ mod tests {
 
     #[test]
     fn check_part2() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part2(&generated), 19);
     }
 }
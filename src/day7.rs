@@ -1,3 +1,5 @@
+use crate::error::{lines, number, ParseError};
+
 pub struct File {
     //name: String,
     size: usize,
@@ -14,7 +16,7 @@ pub struct Directory {
 }
 
 #[aoc_generator(day7)]
-pub fn parse_filesystem(input: &str) -> Vec<Directory> {
+pub fn parse_filesystem(input: &str) -> Result<Vec<Directory>, ParseError> {
     let mut dirs = vec![Directory {
         name: "/".to_owned(),
 
@@ -28,16 +30,16 @@ pub fn parse_filesystem(input: &str) -> Vec<Directory> {
     // root has index 0
     let mut cwd = 0;
 
-    for l in input.lines() {
+    for (n, l) in lines(input) {
         let mut split = l.split(' ');
         match split.next() {
             Some("$") => {
                 match split.next() {
                     Some("cd") => match split.next() {
                         Some("..") => {
-                            cwd = dirs[cwd]
-                                .parent
-                                .expect("cannot cd .. out of root directory!")
+                            cwd = dirs[cwd].parent.ok_or_else(|| {
+                                ParseError::line(n, l, "cannot cd .. out of root directory")
+                            })?
                         }
                         Some("/") => cwd = 0,
                         Some(cd) => {
@@ -51,16 +53,20 @@ pub fn parse_filesystem(input: &str) -> Vec<Directory> {
                                         None
                                     }
                                 })
-                                .expect("couldn't find cd dir!");
+                                .ok_or_else(|| ParseError::token(n, l, cd, "no such directory"))?;
                         }
-                        _ => unreachable!(),
+                        None => return Err(ParseError::end(n, l, "expected a directory")),
                     },
                     Some("ls") => continue, // noop
-                    _ => unreachable!(),
+                    Some(cmd) => return Err(ParseError::token(n, l, cmd, "unknown command")),
+                    None => return Err(ParseError::end(n, l, "expected a command")),
                 }
             }
             Some("dir") => {
-                let name = split.next().unwrap().to_owned();
+                let name = split
+                    .next()
+                    .ok_or_else(|| ParseError::end(n, l, "expected a directory name"))?
+                    .to_owned();
 
                 let k = dirs.len();
 
@@ -75,17 +81,17 @@ pub fn parse_filesystem(input: &str) -> Vec<Directory> {
                 dirs[cwd].directories.push(k);
             }
             Some(s) => {
-                let size = s.parse::<usize>().unwrap();
+                let size = number(n, l, s)?;
                 // actually unused
                 //let name = split.next().unwrap().to_owned();
 
                 dirs[cwd].files.push(File { size });
             }
-            _ => unreachable!(),
+            None => unreachable!(),
         }
     }
 
-    dirs
+    Ok(dirs)
 }
 
 fn compute_directory_size(cwd: usize, dirs: &[Directory], sizes: &mut [usize]) -> usize {
@@ -149,15 +155,29 @@ $ ls
 5626152 d.ext
 7214296 k";
 
+    #[test]
+    fn check_malformed() {
+        let err = super::parse_filesystem("$ cd /\n$ cd a").err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 6: no such directory, found `a`"
+        );
+
+        let err = super::parse_filesystem("$ cd /\n$ ls\n12a b.txt")
+            .err()
+            .unwrap();
+        assert_eq!((err.line, err.column), (3, 1));
+    }
+
     #[test]
     fn check_part1() {
-        let generated = super::parse_filesystem(EXAMPLE);
+        let generated = super::parse_filesystem(EXAMPLE).unwrap();
         assert_eq!(super::solve_part1(&generated), 95437);
     }
 
     #[test]
     fn check_part2() {
-        let generated = super::parse_filesystem(EXAMPLE);
+        let generated = super::parse_filesystem(EXAMPLE).unwrap();
         assert_eq!(super::solve_part2(&generated), 24933642);
     }
 }
@@ -1,92 +1,46 @@
-use std::iter;
+use crate::{error::ParseError, grid::Grid, vec::Vec2};
 
-use itertools::Itertools;
-
-type TreeGrid = Vec<Vec<u32>>;
+type TreeGrid = Grid<u32>;
 
 #[aoc_generator(day8)]
-pub fn parse(input: &str) -> TreeGrid {
-    input
-        .lines()
-        .map(|l| l.chars().filter_map(|c| c.to_digit(10)).collect_vec())
-        .collect()
-}
-
-fn occlusion<R>(height: u32, range: R, trees: &TreeGrid) -> (bool, usize)
-where
-    R: Iterator<Item = (usize, usize)>,
-{
-    let mut distance = 0;
-    for (i, j) in range {
-        distance += 1;
-        if trees[i][j] >= height {
-            return (false, distance);
-        }
-    }
-
-    (true, distance)
+pub fn parse(input: &str) -> Result<TreeGrid, ParseError> {
+    Grid::parse(input, "a digit", |c| c.to_digit(10))
 }
 
 // Naive, brute force solution
-fn solve((i, j): (usize, usize), trees: &TreeGrid) -> (bool, usize) {
-    let width = trees[i].len();
-    let height = trees.len();
-
-    let tree_height = trees[i][j];
+fn solve(p: Vec2, trees: &TreeGrid) -> (bool, usize) {
+    let tree_height = trees[p];
 
     let mut visible = false;
     let mut scenic = 1;
 
-    // left
-    let (v, d) = occlusion(tree_height, iter::repeat(i).zip((0..j).rev()), trees);
-    visible |= v;
-    scenic *= d;
-
-    // right
-    let (v, d) = occlusion(tree_height, iter::repeat(i).zip(j + 1..width), trees);
-    visible |= v;
-    scenic *= d;
-
-    // top
-    let (v, d) = occlusion(tree_height, ((0..i).rev()).zip(iter::repeat(j)), trees);
-    visible |= v;
-    scenic *= d;
-
-    // bottom
-    let (v, d) = occlusion(tree_height, (i + 1..height).zip(iter::repeat(j)), trees);
-    visible |= v;
-    scenic *= d;
+    for dir in [Vec2::LEFT, Vec2::RIGHT, Vec2::UP, Vec2::DOWN] {
+        let mut distance = 0;
+        let mut occluded = false;
+        for (_, &height) in trees.ray(p, dir) {
+            distance += 1;
+            if height >= tree_height {
+                occluded = true;
+                break;
+            }
+        }
+        visible |= !occluded;
+        scenic *= distance;
+    }
 
     (visible, scenic)
 }
 
 #[aoc(day8, part1)]
 pub fn solve_part1(trees: &TreeGrid) -> usize {
-    trees
-        .iter()
-        .enumerate()
-        .map(|(i, row)| {
-            row.iter()
-                .enumerate()
-                .map(|(j, _)| solve((i, j), trees).0)
-                .filter(|v| *v)
-                .count()
-        })
-        .sum::<usize>()
+    trees.positions().filter(|&p| solve(p, trees).0).count()
 }
 
 #[aoc(day8, part2)]
 pub fn solve_part2(trees: &TreeGrid) -> usize {
     trees
-        .iter()
-        .enumerate()
-        .map(|(i, row)| {
-            row.iter()
-                .enumerate()
-                .map(|(j, _)| solve((i, j), trees).1)
-                .max()
-                .unwrap_or_default()
-        })
+        .positions()
+        .map(|p| solve(p, trees).1)
         .max()
         .unwrap_or_default()
 }
@@ -102,13 +56,13 @@ mod tests {
 
     #[test]
     fn check_part1() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part1(&generated), 21);
     }
 
     #[test]
     fn check_part2() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part2(&generated), 8);
     }
 }
@@ -1,7 +1,12 @@
 use std::{collections::HashSet, iter};
 
+use crate::{
+    error::{lines, number, ParseError},
+    vec::Vec2,
+};
+
 type Move = (char, i32);
-type Position = (i32, i32);
+type Position = Vec2;
 
 struct Rope<const SIZE: usize> {
     knots: [Position; SIZE],
@@ -11,18 +16,15 @@ struct Rope<const SIZE: usize> {
 impl<const SIZE: usize> Rope<SIZE> {
     fn new() -> Rope<SIZE> {
         let mut rope = Rope {
-            knots: [(0, 0); SIZE],
+            knots: [Vec2::ZERO; SIZE],
             visited: HashSet::new(),
         };
-        rope.visited.insert((0, 0));
+        rope.visited.insert(Vec2::ZERO);
         rope
     }
 
-    fn step(&mut self, (dx, dy): Position) {
-        let mut head = &mut self.knots[0];
-
-        head.0 += dx;
-        head.1 += dy;
+    fn step(&mut self, direction: Position) {
+        self.knots[0] += direction;
 
         for i in 1..SIZE {
             if !self.touching(i) {
@@ -34,42 +36,31 @@ impl<const SIZE: usize> Rope<SIZE> {
     }
 
     fn touching(&self, i: usize) -> bool {
-        let before = self.knots[i - 1];
-        let current = self.knots[i];
-
-        let dx = (before.0 - current.0).abs();
-        let dy = (before.1 - current.1).abs();
-
-        dx < 2 && dy < 2
+        self.knots[i - 1].chebyshev(self.knots[i]) < 2
     }
 
     fn repair(&mut self, i: usize) {
         let before = self.knots[i - 1];
-        let mut current = &mut self.knots[i];
-
-        let dx = before.0 - current.0;
-        let dy = before.1 - current.1;
+        let current = &mut self.knots[i];
 
-        current.0 += dx.signum();
-        current.1 += dy.signum();
+        *current += (before - *current).signum();
     }
 }
 
 #[aoc_generator(day9)]
-pub fn parse(input: &str) -> Vec<Move> {
-    input
-        .lines()
-        .filter_map(|l| {
-            let mut split = l.split(' ');
-
-            let direction = split.next().and_then(|s| s.chars().next());
-            let amount = split.next().and_then(|n| n.parse::<i32>().ok());
-
-            if let (Some(direction), Some(amount)) = (direction, amount) {
-                Some((direction, amount))
-            } else {
-                None
-            }
+pub fn parse(input: &str) -> Result<Vec<Move>, ParseError> {
+    lines(input)
+        .map(|(n, l)| {
+            let (direction, amount) = l
+                .split_once(' ')
+                .ok_or_else(|| ParseError::line(n, l, "expected a direction and amount"))?;
+
+            let direction = match direction {
+                "R" | "L" | "U" | "D" => direction.chars().next().unwrap(),
+                _ => return Err(ParseError::token(n, l, direction, "expected R, L, U or D")),
+            };
+
+            Ok((direction, number(n, l, amount)?))
         })
         .collect()
 }
@@ -78,15 +69,16 @@ fn make_steps<const SIZE: usize>(moves: &[Move]) -> usize {
     let mut rope = Rope::<SIZE>::new();
 
     for (direction, amount) in moves {
-        for step in iter::repeat(match direction {
-            'R' => (1, 0),
-            'L' => (-1, 0),
-            'U' => (0, 1),
-            'D' => (0, -1),
-            _ => unreachable!(),
-        })
-        .take(*amount as usize)
-        {
+        for step in iter::repeat_n(
+            match direction {
+                'R' => Vec2::RIGHT,
+                'L' => Vec2::LEFT,
+                'U' => Vec2::UP,
+                'D' => Vec2::DOWN,
+                _ => unreachable!(),
+            },
+            *amount as usize,
+        ) {
             rope.step(step);
         }
     }
@@ -110,30 +102,30 @@ mod tests {
     #[test]
     fn check_part1() {
         const EXAMPLE: &str = "R 4
-        U 4
-        L 3
-        D 1
-        R 4
-        D 1
-        L 5
-        R 2";
-
-        let generated = super::parse(EXAMPLE);
+U 4
+L 3
+D 1
+R 4
+D 1
+L 5
+R 2";
+
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part1(&generated), 13);
     }
 
     #[test]
     fn check_part2() {
         const EXAMPLE: &str = "R 5
-        U 8
-        L 8
-        D 3
-        R 17
-        D 10
-        L 25
-        U 20";
-
-        let generated = super::parse(EXAMPLE);
+U 8
+L 8
+D 3
+R 17
+D 10
+L 25
+U 20";
+
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part2(&generated), 36);
     }
 }
@@ -0,0 +1,107 @@
+use std::{error::Error, fmt::Display, str::FromStr};
+
+/// Malformed or truncated puzzle input, as reported by the generators.
+///
+/// `line` and `column` are 1-based, `text` is the offending part of the line
+/// (empty when something is missing).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+    pub message: String,
+}
+
+impl ParseError {
+    // the whole line is wrong
+    pub fn line(line: usize, text: &str, message: impl Into<String>) -> Self {
+        ParseError {
+            line,
+            column: 1,
+            text: text.to_owned(),
+            message: message.into(),
+        }
+    }
+
+    // `token` is wrong, it should be a slice of the line `text`
+    pub fn token(line: usize, text: &str, token: &str, message: impl Into<String>) -> Self {
+        let start = text.as_ptr() as usize;
+        let offset = (token.as_ptr() as usize)
+            .checked_sub(start)
+            .filter(|&offset| offset + token.len() <= text.len())
+            .or_else(|| text.find(token))
+            .unwrap_or(0);
+
+        ParseError {
+            line,
+            column: text[..offset].chars().count() + 1,
+            text: token.to_owned(),
+            message: message.into(),
+        }
+    }
+
+    // the line (or input if `text` is empty) ends before something expected
+    pub fn end(line: usize, text: &str, message: impl Into<String>) -> Self {
+        ParseError {
+            line,
+            column: text.chars().count() + 1,
+            text: String::new(),
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )?;
+        if !self.text.is_empty() {
+            write!(f, ", found `{}`", self.text)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ParseError {}
+
+/// Lines of the input numbered from 1, as used in [`ParseError`].
+pub fn lines(input: &str) -> impl Iterator<Item = (usize, &str)> + Clone {
+    input.lines().enumerate().map(|(i, l)| (i + 1, l))
+}
+
+/// Parses `token`, a slice of the line `text`, as a number.
+pub fn number<T: FromStr>(line: usize, text: &str, token: &str) -> Result<T, ParseError> {
+    token
+        .parse()
+        .map_err(|_| ParseError::token(line, text, token, "expected a number"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ParseError;
+
+    #[test]
+    fn check_token() {
+        let text = "addx 1x5";
+        let err = super::number::<i32>(3, text, &text[5..]).unwrap_err();
+        assert_eq!((err.line, err.column), (3, 6));
+        assert_eq!(
+            err.to_string(),
+            "line 3, column 6: expected a number, found `1x5`"
+        );
+
+        // tokens of a copy of the line are located by searching
+        let copy = text.to_owned();
+        let err = ParseError::token(1, text, &copy[5..], "oops");
+        assert_eq!(err.column, 6);
+    }
+
+    #[test]
+    fn check_end() {
+        let err = ParseError::end(2, "move 1 from", "expected a stack");
+        assert_eq!(err.to_string(), "line 2, column 12: expected a stack");
+    }
+}
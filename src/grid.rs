@@ -0,0 +1,311 @@
+use std::{
+    fmt::Display,
+    ops::{Index, IndexMut},
+};
+
+use crate::{
+    error::{lines, ParseError},
+    vec::Vec2,
+};
+
+const ADJ4: [Vec2; 4] = [Vec2::UP, Vec2::RIGHT, Vec2::DOWN, Vec2::LEFT];
+
+const ADJ8: [Vec2; 8] = [
+    Vec2 { x: 0, y: -1 },
+    Vec2 { x: 1, y: -1 },
+    Vec2 { x: 1, y: 0 },
+    Vec2 { x: 1, y: 1 },
+    Vec2 { x: 0, y: 1 },
+    Vec2 { x: -1, y: 1 },
+    Vec2 { x: -1, y: 0 },
+    Vec2 { x: -1, y: -1 },
+];
+
+/// Dense rectangular grid indexed by [`Vec2`], `(0, 0)` is the top left
+/// and y increases downwards.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    /// Builds a grid from rows of equal length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |row| row.len());
+        assert!(rows.iter().all(|row| row.len() == width), "ragged rows");
+
+        Grid {
+            width,
+            height,
+            cells: rows.into_iter().flatten().collect(),
+        }
+    }
+
+    /// Parses one cell per character, `cell` returns `None` for characters
+    /// that aren't `expected`.
+    pub fn parse<F>(input: &str, expected: &str, mut cell: F) -> Result<Self, ParseError>
+    where
+        F: FnMut(char) -> Option<T>,
+    {
+        let mut rows = vec![];
+        for (n, l) in lines(input) {
+            let row = l
+                .char_indices()
+                .map(|(i, c)| {
+                    cell(c).ok_or_else(|| {
+                        let token = &l[i..i + c.len_utf8()];
+                        ParseError::token(n, l, token, format!("expected {expected}"))
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if rows
+                .first()
+                .is_some_and(|first: &Vec<T>| first.len() != row.len())
+            {
+                return Err(ParseError::line(n, l, "rows differ in length"));
+            }
+            rows.push(row);
+        }
+        Ok(Grid::from_rows(rows))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, p: Vec2) -> bool {
+        p.x >= 0 && p.y >= 0 && (p.x as usize) < self.width && (p.y as usize) < self.height
+    }
+
+    fn index_of(&self, p: Vec2) -> Option<usize> {
+        if self.contains(p) {
+            Some(p.y as usize * self.width + p.x as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, p: Vec2) -> Option<&T> {
+        self.index_of(p).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, p: Vec2) -> Option<&mut T> {
+        self.index_of(p).map(|i| &mut self.cells[i])
+    }
+
+    /// All positions in row-major order.
+    pub fn positions(&self) -> impl Iterator<Item = Vec2> {
+        let width = self.width;
+        (0..self.width * self.height).map(move |i| Vec2 {
+            x: (i % width) as i32,
+            y: (i / width) as i32,
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Vec2, &T)> + '_ {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn find<F>(&self, mut predicate: F) -> Option<Vec2>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.iter().find(|(_, c)| predicate(c)).map(|(p, _)| p)
+    }
+
+    /// Orthogonal neighbours of `p` inside the grid.
+    pub fn neighbors4(&self, p: Vec2) -> impl Iterator<Item = Vec2> + '_ {
+        ADJ4.iter()
+            .map(move |&d| p + d)
+            .filter(|&n| self.contains(n))
+    }
+
+    /// Orthogonal and diagonal neighbours of `p` inside the grid.
+    pub fn neighbors8(&self, p: Vec2) -> impl Iterator<Item = Vec2> + '_ {
+        ADJ8.iter()
+            .map(move |&d| p + d)
+            .filter(|&n| self.contains(n))
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        self.cells.iter().skip(x).step_by(self.width.max(1))
+    }
+
+    /// Cells from `from` (exclusive) in steps of `dir` until leaving the grid.
+    pub fn ray(&self, from: Vec2, dir: Vec2) -> impl Iterator<Item = (Vec2, &T)> + '_ {
+        let mut p = from;
+        std::iter::from_fn(move || {
+            p += dir;
+            self.get(p).map(|c| (p, c))
+        })
+    }
+
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    fn remap<F>(&self, width: usize, height: usize, source: F) -> Self
+    where
+        T: Clone,
+        F: Fn(usize, usize) -> (usize, usize),
+    {
+        let cells = (0..width * height)
+            .map(|i| {
+                let (x, y) = source(i % width, i / width);
+                self.cells[y * self.width + x].clone()
+            })
+            .collect();
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.height, self.width, |x, y| (y, x))
+    }
+
+    /// Rotates by 90 degrees clockwise.
+    pub fn rotate_cw(&self) -> Self
+    where
+        T: Clone,
+    {
+        let h = self.height;
+        self.remap(self.height, self.width, |x, y| (y, h - 1 - x))
+    }
+
+    /// Rotates by 90 degrees counterclockwise.
+    pub fn rotate_ccw(&self) -> Self
+    where
+        T: Clone,
+    {
+        let w = self.width;
+        self.remap(self.height, self.width, |x, y| (w - 1 - y, x))
+    }
+}
+
+impl<T> Index<Vec2> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Vec2) -> &Self::Output {
+        self.get(p)
+            .unwrap_or_else(|| panic!("{p:?} is outside of the grid"))
+    }
+}
+
+impl<T> IndexMut<Vec2> for Grid<T> {
+    fn index_mut(&mut self, p: Vec2) -> &mut Self::Output {
+        self.get_mut(p)
+            .unwrap_or_else(|| panic!("{p:?} is outside of the grid"))
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows() {
+            for c in row {
+                write!(f, "{c}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Grid;
+    use crate::vec::Vec2;
+
+    const EXAMPLE: &str = "ab\ncd\nef";
+
+    fn parse(input: &str) -> Grid<char> {
+        Grid::parse(input, "a letter", |c| {
+            Some(c).filter(char::is_ascii_lowercase)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn check_parse() {
+        let grid = parse(EXAMPLE);
+        assert_eq!((grid.width(), grid.height()), (2, 3));
+        assert_eq!(grid[Vec2 { x: 1, y: 2 }], 'f');
+        assert_eq!(grid.get(Vec2 { x: 2, y: 0 }), None);
+        assert_eq!(grid.to_string(), "ab\ncd\nef\n");
+
+        let err = Grid::parse("ab\nc!", "a letter", |c| {
+            Some(c).filter(char::is_ascii_lowercase)
+        });
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "line 2, column 2: expected a letter, found `!`"
+        );
+        let err = Grid::parse("ab\nc", "a letter", Some);
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "line 2, column 1: rows differ in length, found `c`"
+        );
+    }
+
+    #[test]
+    fn check_iterators() {
+        let grid = parse(EXAMPLE);
+        assert_eq!(grid.row(1), ['c', 'd']);
+        assert_eq!(grid.column(1).collect::<String>(), "bdf");
+        assert_eq!(grid.neighbors4(Vec2 { x: 0, y: 0 }).count(), 2);
+        assert_eq!(grid.neighbors8(Vec2 { x: 0, y: 1 }).count(), 5);
+
+        let ray = grid.ray(Vec2 { x: 0, y: 0 }, Vec2 { x: 1, y: 1 });
+        assert_eq!(ray.map(|(_, c)| *c).collect::<String>(), "d");
+        assert_eq!(grid.find(|&c| c == 'e'), Some(Vec2 { x: 0, y: 2 }));
+    }
+
+    #[test]
+    fn check_transforms() {
+        let grid = parse(EXAMPLE);
+        assert_eq!(grid.transpose().to_string(), "ace\nbdf\n");
+        assert_eq!(grid.rotate_cw().to_string(), "eca\nfdb\n");
+        assert_eq!(grid.rotate_ccw().to_string(), "bdf\nace\n");
+        assert_eq!(grid.rotate_cw().rotate_ccw(), grid);
+        assert_eq!(grid.map(|c| c.to_ascii_uppercase()).row(0), ['A', 'B']);
+    }
+}
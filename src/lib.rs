@@ -30,6 +30,11 @@ pub mod day7;
 pub mod day8;
 pub mod day9;
 
+pub mod cycle;
+pub mod error;
+pub mod grid;
+pub mod ocr;
+pub mod search;
 pub mod vec;
 
 aoc_lib! { year = 2022 }
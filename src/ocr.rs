@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+// the letters of a font rendered side by side, one every `pitch` columns
+struct Font {
+    letters: &'static str,
+    glyphs: &'static str,
+    pitch: usize,
+}
+
+// 4x6 font of the CRT (2016 day 8, 2019 day 8 & 11, 2021 day 13, 2022 day 10)
+const SMALL: Font = Font {
+    letters: "ABCEFGHIJKLOPRSUYZ",
+    pitch: 5,
+    glyphs:
+        ".##..###...##..####.####..##..#..#.###....##.#..#.#.....##..###..###...###.#..#.#...#####
+#..#.#..#.#..#.#....#....#..#.#..#..#......#.#.#..#....#..#.#..#.#..#.#....#..#.#...#...#
+#..#.###..#....###..###..#....####..#......#.##...#....#..#.#..#.#..#.#....#..#..#.#...#.
+####.#..#.#....#....#....#.##.#..#..#......#.#.#..#....#..#.###..###...##..#..#...#...#..
+#..#.#..#.#..#.#....#....#..#.#..#..#...#..#.#.#..#....#..#.#....#.#.....#.#..#...#..#...
+#..#.###...##..####.#.....###.#..#.###...##..#..#.####..##..#....#..#.###...##....#..####",
+};
+
+// 6x10 font of the night sky messages (2018 day 10)
+const LARGE: Font = Font {
+    letters: "ABCEFGHJKLNPRXZ",
+    pitch: 8,
+    glyphs: "..##....#####....####...######..######...####...#....#.....###..#....#..#.......#....#..#####...#####...#....#..######
+.#..#...#....#..#....#..#.......#.......#....#..#....#......#...#...#...#.......##...#..#....#..#....#..#....#.......#
+#....#..#....#..#.......#.......#.......#.......#....#......#...#..#....#.......##...#..#....#..#....#...#..#........#
+#....#..#....#..#.......#.......#.......#.......#....#......#...#.#.....#.......#.#..#..#....#..#....#...#..#.......#.
+#....#..#####...#.......#####...#####...#.......######......#...##......#.......#.#..#..#####...#####.....##.......#..
+######..#....#..#.......#.......#.......#..###..#....#......#...##......#.......#..#.#..#.......#..#......##......#...
+#....#..#....#..#.......#.......#.......#....#..#....#......#...#.#.....#.......#..#.#..#.......#...#....#..#....#....
+#....#..#....#..#.......#.......#.......#....#..#....#..#...#...#..#....#.......#...##..#.......#...#....#..#...#.....
+#....#..#....#..#....#..#.......#.......#...##..#....#..#...#...#...#...#.......#...##..#.......#....#..#....#..#.....
+#....#..#####....####...######..#........###.#..#....#...###....#....#..######..#....#..#.......#....#..#....#..######",
+};
+
+fn width<R: AsRef<[char]>>(screen: &[R]) -> usize {
+    screen
+        .iter()
+        .map(|row| row.as_ref().len())
+        .max()
+        .unwrap_or(0)
+}
+
+fn lit<R: AsRef<[char]>>(screen: &[R], x: usize) -> bool {
+    screen.iter().any(|row| row.as_ref().get(x) == Some(&'#'))
+}
+
+// splits a screen into glyphs `pitch` columns wide from the column `start`,
+// lit pixels are '#', leaving out the blank columns at the end
+fn segment<R: AsRef<[char]>>(screen: &[R], start: usize, pitch: usize) -> Vec<String> {
+    let end = (start..width(screen))
+        .rev()
+        .find(|&x| lit(screen, x))
+        .map_or(start, |x| x + 1);
+
+    (start..end)
+        .step_by(pitch)
+        .map(|x| {
+            screen
+                .iter()
+                .map(|row| {
+                    (x..x + pitch)
+                        .map(|i| match row.as_ref().get(i) {
+                            Some('#') => '#',
+                            _ => '.',
+                        })
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect()
+}
+
+/// Reads the letters drawn on a screen of `#` (lit) and `.` (dark) pixels.
+///
+/// The font is picked by the screen height (6 or 10 rows), returns `None`
+/// if the screen is blank or contains a glyph that isn't a known letter.
+/// Letters sit at the fixed pitch of the font, so touching ones are fine.
+pub fn decode<R: AsRef<[char]>>(screen: &[R]) -> Option<String> {
+    let font = match screen.len() {
+        6 => SMALL,
+        10 => LARGE,
+        _ => return None,
+    };
+
+    let glyphs = font
+        .glyphs
+        .lines()
+        .map(|l| l.chars().collect())
+        .collect::<Vec<Vec<char>>>();
+    let letters = segment(&glyphs, 0, font.pitch)
+        .into_iter()
+        .zip(font.letters.chars())
+        .collect::<HashMap<_, _>>();
+
+    // the CRT draws from the left edge, other screens may start anywhere up
+    // to the first lit column (a glyph can start with blank columns)
+    let first = (0..width(screen)).find(|&x| lit(screen, x))?;
+    (first.saturating_sub(font.pitch - 1)..=first).find_map(|start| {
+        segment(screen, start, font.pitch)
+            .iter()
+            .map(|g| letters.get(g))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Font, LARGE, SMALL};
+
+    // `text` in `font` after `offset` blank columns
+    fn render(text: &str, font: &Font, offset: usize) -> Vec<Vec<char>> {
+        let glyphs = font
+            .glyphs
+            .lines()
+            .map(|l| l.chars().collect())
+            .collect::<Vec<Vec<char>>>();
+        let segments = super::segment(&glyphs, 0, font.pitch);
+
+        let mut screen = vec![vec!['.'; offset]; glyphs.len()];
+        for c in text.chars() {
+            let i = font.letters.find(c).unwrap();
+            for (row, line) in screen.iter_mut().zip(segments[i].lines()) {
+                row.extend(line.chars());
+            }
+        }
+        screen
+    }
+
+    #[test]
+    fn check_small() {
+        let screen = render("RGLRBZAU", &SMALL, 0);
+        assert_eq!(super::decode(&screen), Some("RGLRBZAU".to_string()));
+
+        let screen = render(SMALL.letters, &SMALL, 0);
+        assert_eq!(super::decode(&screen), Some(SMALL.letters.to_string()));
+    }
+
+    #[test]
+    fn check_touching() {
+        // Y fills its 5 columns, no blank column separates it from the next
+        let screen = render("YJYZIY", &SMALL, 0);
+        assert_eq!(super::decode(&screen), Some("YJYZIY".to_string()));
+    }
+
+    #[test]
+    fn check_large() {
+        let screen = render("HXNZ", &LARGE, 0);
+        assert_eq!(super::decode(&screen), Some("HXNZ".to_string()));
+
+        // J starts with blank columns, the message with some more
+        let screen = render("JAB", &LARGE, 5);
+        assert_eq!(super::decode(&screen), Some("JAB".to_string()));
+
+        let screen = render(LARGE.letters, &LARGE, 0);
+        assert_eq!(super::decode(&screen), Some(LARGE.letters.to_string()));
+    }
+
+    #[test]
+    fn check_unknown() {
+        let screen = vec![vec!['#', '.', '#']; 6];
+        assert_eq!(super::decode(&screen), None);
+
+        let screen = vec![vec!['.'; 40]; 6];
+        assert_eq!(super::decode(&screen), None);
+    }
+}
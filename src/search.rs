@@ -0,0 +1,213 @@
+use std::{
+    cmp::Reverse,
+    collections::{hash_map::Entry, BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::Hash,
+    ops::Add,
+};
+
+/// A shortest path, `nodes` runs from the start to the goal (both included).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<N, C> {
+    pub cost: C,
+    pub nodes: Vec<N>,
+}
+
+// follows the parent links back from `idx` to the start
+fn reconstruct<N: Clone>(seen: &[(N, usize)], mut idx: usize) -> Vec<N> {
+    let mut nodes = vec![seen[idx].0.clone()];
+    while seen[idx].1 != idx {
+        idx = seen[idx].1;
+        nodes.push(seen[idx].0.clone());
+    }
+    nodes.reverse();
+    nodes
+}
+
+/// Breadth-first search where every step costs 1, stops at the first node
+/// satisfying `goal`.
+pub fn bfs<N, I, S, G>(start: N, mut successors: S, mut goal: G) -> Option<Path<N, usize>>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = N>,
+    S: FnMut(&N) -> I,
+    G: FnMut(&N) -> bool,
+{
+    // (node, parent index), the start is its own parent
+    let mut seen = vec![(start.clone(), 0)];
+    let mut index = HashMap::from([(start, 0)]);
+    let mut cost = vec![0];
+    let mut queue = VecDeque::from([0]);
+
+    while let Some(idx) = queue.pop_front() {
+        if goal(&seen[idx].0) {
+            return Some(Path {
+                cost: cost[idx],
+                nodes: reconstruct(&seen, idx),
+            });
+        }
+
+        for next in successors(&seen[idx].0) {
+            if let Entry::Vacant(e) = index.entry(next.clone()) {
+                e.insert(seen.len());
+                queue.push_back(seen.len());
+                cost.push(cost[idx] + 1);
+                seen.push((next, idx));
+            }
+        }
+    }
+    None
+}
+
+/// All nodes reachable from `start`, including itself.
+pub fn reachable<N, I, S>(start: N, mut successors: S) -> HashSet<N>
+where
+    N: Eq + Hash + Clone,
+    I: IntoIterator<Item = N>,
+    S: FnMut(&N) -> I,
+{
+    let mut seen = HashSet::from([start.clone()]);
+    let mut stack = vec![start];
+
+    while let Some(node) = stack.pop() {
+        for next in successors(&node) {
+            if seen.insert(next.clone()) {
+                stack.push(next);
+            }
+        }
+    }
+    seen
+}
+
+/// Cheapest path with non-negative step costs.
+pub fn dijkstra<N, C, I, S, G>(start: N, successors: S, goal: G) -> Option<Path<N, C>>
+where
+    N: Eq + Hash + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+    S: FnMut(&N) -> I,
+    G: FnMut(&N) -> bool,
+{
+    astar(start, successors, |_| C::default(), goal)
+}
+
+/// Cheapest path guided by `heuristic`, which must never overestimate the
+/// remaining cost to a goal.
+pub fn astar<N, C, I, S, H, G>(
+    start: N,
+    mut successors: S,
+    mut heuristic: H,
+    mut goal: G,
+) -> Option<Path<N, C>>
+where
+    N: Eq + Hash + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+    S: FnMut(&N) -> I,
+    H: FnMut(&N) -> C,
+    G: FnMut(&N) -> bool,
+{
+    let zero = C::default();
+    let mut seen = vec![(start.clone(), 0)];
+    let mut cost = vec![zero];
+    let mut index = HashMap::from([(start.clone(), 0)]);
+
+    // ties are broken towards the larger cost, i.e. closer to the goal
+    let mut queue = BinaryHeap::from([(Reverse(heuristic(&start)), zero, 0)]);
+
+    while let Some((_, c, idx)) = queue.pop() {
+        if c > cost[idx] {
+            continue;
+        }
+        if goal(&seen[idx].0) {
+            return Some(Path {
+                cost: c,
+                nodes: reconstruct(&seen, idx),
+            });
+        }
+
+        for (next, step) in successors(&seen[idx].0) {
+            let nc = c + step;
+            let nidx = match index.entry(next.clone()) {
+                Entry::Vacant(e) => {
+                    e.insert(seen.len());
+                    seen.push((next.clone(), idx));
+                    cost.push(nc);
+                    seen.len() - 1
+                }
+                Entry::Occupied(e) => {
+                    let nidx = *e.get();
+                    if nc >= cost[nidx] {
+                        continue;
+                    }
+                    seen[nidx].1 = idx;
+                    cost[nidx] = nc;
+                    nidx
+                }
+            };
+            queue.push((Reverse(nc + heuristic(&next)), nc, nidx));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{grid::Grid, vec::Vec2};
+
+    const MAZE: &str = "..#....
+.##.##.
+...#...
+.#...#.";
+
+    fn maze() -> Grid<bool> {
+        Grid::parse(MAZE, "'#' or '.'", |c| match c {
+            '#' => Some(false),
+            '.' => Some(true),
+            _ => None,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn check_bfs() {
+        let grid = maze();
+        let goal = Vec2::new(6, 0);
+        let open = |&p: &Vec2| grid.neighbors4(p).filter(|&n| grid[n]).collect::<Vec<_>>();
+
+        let path = super::bfs(Vec2::ZERO, open, |&p| p == goal).unwrap();
+        assert_eq!(path.cost, 12);
+        assert_eq!(path.nodes.len(), 13);
+        assert_eq!(path.nodes.first(), Some(&Vec2::ZERO));
+        assert_eq!(path.nodes.last(), Some(&goal));
+        assert!(path.nodes.windows(2).all(|w| w[0].manhattan(w[1]) == 1));
+
+        let walled = super::bfs(Vec2::ZERO, |_| vec![], |&p| p == goal);
+        assert_eq!(walled, None);
+        assert_eq!(super::reachable(Vec2::new(2, 3), open).len(), 20);
+    }
+
+    #[test]
+    fn check_weighted() {
+        // walking through a wall costs 10
+        let grid = maze();
+        let goal = Vec2::new(6, 0);
+        let successors = |&p: &Vec2| {
+            grid.neighbors4(p)
+                .map(|n| (n, if grid[n] { 1 } else { 10 }))
+                .collect::<Vec<_>>()
+        };
+
+        let dijkstra = super::dijkstra(Vec2::ZERO, successors, |&p| p == goal).unwrap();
+        assert_eq!(dijkstra.cost, 12);
+
+        let astar = super::astar(
+            Vec2::ZERO,
+            successors,
+            |&p| p.manhattan(goal),
+            |&p| p == goal,
+        )
+        .unwrap();
+        assert_eq!(astar.cost, 12);
+        assert_eq!(astar.nodes.len(), 13);
+    }
+}
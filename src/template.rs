@@ -1,9 +1,11 @@
 use itertools::Itertools;
 
+use crate::error::{lines, ParseError};
+
 type Data = String;
 
 #[aoc_generator(dayX)]
-pub fn parse(input: &str) -> Data {
+pub fn parse(input: &str) -> Result<Data, ParseError> {
     todo!()
 }
 
@@ -23,13 +25,13 @@ mod tests {
 
     #[test]
     fn check_part1() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part1(&generated), 0);
     }
 
     #[test]
     fn check_part2() {
-        let generated = super::parse(EXAMPLE);
+        let generated = super::parse(EXAMPLE).unwrap();
         assert_eq!(super::solve_part2(&generated), 0);
     }
 }
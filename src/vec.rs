@@ -1,44 +1,266 @@
-use std::ops::{Add, AddAssign, Mul, Sub};
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
-pub struct Vec2 {
-    pub x: i32,
-    pub y: i32,
+/// Signed integers usable as vector components.
+pub trait Scalar:
+    Copy
+    + Ord
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const NEG_ONE: Self;
+
+    fn abs(self) -> Self;
+    fn signum(self) -> Self;
 }
 
-impl Add for Vec2 {
-    type Output = Vec2;
-    fn add(self, rhs: Self) -> Self::Output {
-        Vec2 {
-            x: self.x + rhs.x,
-            y: self.y + rhs.y,
+macro_rules! impl_scalar {
+    ($($t:ty),*) => {
+        $(
+            impl Scalar for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                const NEG_ONE: Self = -1;
+
+                fn abs(self) -> Self {
+                    <$t>::abs(self)
+                }
+
+                fn signum(self) -> Self {
+                    <$t>::signum(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_scalar!(i8, i16, i32, i64, i128, isize);
+
+/// 2D vector, in grid coordinates `(0, 0)` is the top left and y increases
+/// downwards.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct Vec2<T = i32> {
+    pub x: T,
+    pub y: T,
+}
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct Vec3<T = i32> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+// component-wise operators shared by both vectors
+macro_rules! impl_ops {
+    ($v:ident { $($c:ident),* }) => {
+        impl<T: Scalar> Add for $v<T> {
+            type Output = Self;
+            fn add(self, rhs: Self) -> Self::Output {
+                $v { $($c: self.$c + rhs.$c),* }
+            }
         }
-    }
+
+        impl<T: Scalar> AddAssign for $v<T> {
+            fn add_assign(&mut self, rhs: Self) {
+                $(self.$c += rhs.$c;)*
+            }
+        }
+
+        impl<T: Scalar> Sub for $v<T> {
+            type Output = Self;
+            fn sub(self, rhs: Self) -> Self::Output {
+                $v { $($c: self.$c - rhs.$c),* }
+            }
+        }
+
+        impl<T: Scalar> SubAssign for $v<T> {
+            fn sub_assign(&mut self, rhs: Self) {
+                $(self.$c -= rhs.$c;)*
+            }
+        }
+
+        impl<T: Scalar> Neg for $v<T> {
+            type Output = Self;
+            fn neg(self) -> Self::Output {
+                $v { $($c: -self.$c),* }
+            }
+        }
+
+        impl<T: Scalar> Mul<T> for $v<T> {
+            type Output = Self;
+            fn mul(self, rhs: T) -> Self::Output {
+                $v { $($c: self.$c * rhs),* }
+            }
+        }
+
+        impl<T: Scalar> $v<T> {
+            pub const ZERO: Self = $v { $($c: T::ZERO),* };
+
+            pub fn dot(self, rhs: Self) -> T {
+                T::ZERO $(+ self.$c * rhs.$c)*
+            }
+
+            pub fn signum(self) -> Self {
+                $v { $($c: self.$c.signum()),* }
+            }
+
+            pub fn abs(self) -> Self {
+                $v { $($c: self.$c.abs()),* }
+            }
+
+            /// Sum of the absolute differences.
+            pub fn manhattan(self, other: Self) -> T {
+                let d = (self - other).abs();
+                T::ZERO $(+ d.$c)*
+            }
+
+            /// Largest absolute difference, the number of king moves.
+            pub fn chebyshev(self, other: Self) -> T {
+                let d = (self - other).abs();
+                T::ZERO $(.max(d.$c))*
+            }
+        }
+    };
 }
 
-impl AddAssign for Vec2 {
-    fn add_assign(&mut self, rhs: Self) {
-        self.x += rhs.x;
-        self.y += rhs.y;
+impl_ops!(Vec2 { x, y });
+impl_ops!(Vec3 { x, y, z });
+
+impl<T: Scalar> Vec2<T> {
+    pub const UP: Self = Vec2 {
+        x: T::ZERO,
+        y: T::NEG_ONE,
+    };
+    pub const DOWN: Self = Vec2 {
+        x: T::ZERO,
+        y: T::ONE,
+    };
+    pub const LEFT: Self = Vec2 {
+        x: T::NEG_ONE,
+        y: T::ZERO,
+    };
+    pub const RIGHT: Self = Vec2 {
+        x: T::ONE,
+        y: T::ZERO,
+    };
+
+    pub const fn new(x: T, y: T) -> Self {
+        Vec2 { x, y }
     }
-}
 
-impl Sub for Vec2 {
-    type Output = Vec2;
-    fn sub(self, rhs: Self) -> Self::Output {
+    /// Turns by 90 degrees clockwise as seen on screen, `RIGHT` becomes `DOWN`.
+    pub fn rotate_cw(self) -> Self {
         Vec2 {
-            x: self.x - rhs.x,
-            y: self.y - rhs.y,
+            x: -self.y,
+            y: self.x,
         }
     }
-}
 
-impl Mul<i32> for Vec2 {
-    type Output = Vec2;
-    fn mul(self, rhs: i32) -> Self::Output {
+    /// Turns by 90 degrees counterclockwise as seen on screen, `RIGHT`
+    /// becomes `UP`.
+    pub fn rotate_ccw(self) -> Self {
         Vec2 {
-            x: self.x * rhs,
-            y: self.y * rhs,
+            x: self.y,
+            y: -self.x,
         }
     }
 }
+
+impl<T: Scalar> Vec3<T> {
+    pub const X: Self = Vec3 {
+        x: T::ONE,
+        y: T::ZERO,
+        z: T::ZERO,
+    };
+    pub const Y: Self = Vec3 {
+        x: T::ZERO,
+        y: T::ONE,
+        z: T::ZERO,
+    };
+    pub const Z: Self = Vec3 {
+        x: T::ZERO,
+        y: T::ZERO,
+        z: T::ONE,
+    };
+
+    pub const fn new(x: T, y: T, z: T) -> Self {
+        Vec3 { x, y, z }
+    }
+
+    pub fn cross(self, rhs: Self) -> Self {
+        Vec3 {
+            x: self.y * rhs.z - self.z * rhs.y,
+            y: self.z * rhs.x - self.x * rhs.z,
+            z: self.x * rhs.y - self.y * rhs.x,
+        }
+    }
+
+    /// Turns by 90 degrees around the unit vector `axis`, counterclockwise
+    /// when looking against it (right-hand rule).
+    pub fn rotate(self, axis: Self) -> Self {
+        axis * axis.dot(self) + axis.cross(self)
+    }
+}
+
+impl<T> From<(T, T)> for Vec2<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Vec2 { x, y }
+    }
+}
+
+impl<T> From<Vec2<T>> for (T, T) {
+    fn from(v: Vec2<T>) -> Self {
+        (v.x, v.y)
+    }
+}
+
+impl<T> From<(T, T, T)> for Vec3<T> {
+    fn from((x, y, z): (T, T, T)) -> Self {
+        Vec3 { x, y, z }
+    }
+}
+
+impl<T> From<Vec3<T>> for (T, T, T) {
+    fn from(v: Vec3<T>) -> Self {
+        (v.x, v.y, v.z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Vec2, Vec3};
+
+    #[test]
+    fn check_vec2() {
+        let a = Vec2::new(3, -4);
+        let b: Vec2 = (1, 1).into();
+        assert_eq!(a - b, Vec2::new(2, -5));
+        assert_eq!(-a * 2, Vec2::new(-6, 8));
+        assert_eq!(a.dot(b), -1);
+        assert_eq!(a.manhattan(Vec2::ZERO), 7);
+        assert_eq!(a.chebyshev(b), 5);
+        assert_eq!(a.signum(), Vec2::new(1, -1));
+
+        assert_eq!(Vec2::<i64>::RIGHT.rotate_cw(), Vec2::DOWN);
+        assert_eq!(Vec2::<i64>::RIGHT.rotate_ccw(), Vec2::UP);
+        assert_eq!(a.rotate_cw().rotate_cw(), -a);
+        assert_eq!(<(i32, i32)>::from(Vec2::LEFT + Vec2::UP), (-1, -1));
+    }
+
+    #[test]
+    fn check_vec3() {
+        let mut a = Vec3::new(1, 2, 3);
+        a -= Vec3::X;
+        assert_eq!(a, Vec3::new(0, 2, 3));
+        assert_eq!(a.manhattan(Vec3::ZERO), 5);
+        assert_eq!(Vec3::<i32>::X.cross(Vec3::Y), Vec3::Z);
+        assert_eq!(Vec3::<i32>::X.rotate(Vec3::Z), Vec3::Y);
+        assert_eq!(a.rotate(Vec3::X).rotate(-Vec3::X), a);
+    }
+}